@@ -0,0 +1,86 @@
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, Attr, HasStringParts, StringPart};
+
+/// The whitespace in front of the line `node` starts on.
+pub fn line_indent(node: &SyntaxNode) -> String {
+    node.prev_sibling_or_token()
+        .and_then(|element| element.into_token())
+        .filter(is_blank)
+        .and_then(|token| token.text().rsplit('\n').next().map(str::to_string))
+        .unwrap_or_default()
+}
+
+pub fn is_blank(token: &syntax::SyntaxToken) -> bool {
+    token.kind().is_trivia() && token.text().trim().is_empty()
+}
+
+pub fn child_index(parent: &SyntaxNode, child: &SyntaxNode) -> usize {
+    parent
+        .children_with_tokens()
+        .position(|c| c.as_node() == Some(child))
+        .expect("node must be child of its parent")
+}
+
+/// The whole file, including comments around the expression and the final newline.
+pub fn serialize_document(root: &SyntaxNode) -> String {
+    root.to_string()
+}
+
+const KEYWORDS: [&str; 9] = ["if", "then", "else", "assert", "with", "let", "in", "rec", "inherit"];
+
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    !KEYWORDS.contains(&name)
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || "_-'".contains(c))
+}
+
+/// `text` as a double quoted Nix string.
+pub fn string_literal(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace("${", "\\${"))
+}
+
+/// The attribute name as it has to be written in an `inherit` or binding.
+pub fn attr_text(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        string_literal(name)
+    }
+}
+
+pub fn attr_key(attr: &Attr) -> String {
+    match attr {
+        Attr::Name(name) => name.token()
+            .map(|t| t.text().to_string())
+            .unwrap_or_default(),
+        Attr::String(string) => {
+            let parts = string.string_parts().collect::<Vec<_>>();
+            if parts.iter().all(|part| matches!(part, StringPart::Fragment(_))) {
+                parts.iter().filter_map(|part| match part {
+                    StringPart::Fragment(text) => Some(text.text().to_string()),
+                    _ => None,
+                }).collect::<Vec<String>>().join("")
+            } else {
+                string.syntax().text().to_string()
+            }
+        },
+        Attr::Dynamic(dynamic) => dynamic.syntax().text().to_string(),
+    }
+}
+
+/// The parenthesized source of `inherit (source) names;`.
+pub fn inherit_source(inherit: &SyntaxNode) -> Option<SyntaxNode> {
+    inherit.children().find(|child| child.kind() == SyntaxKind::PAREN)
+}
+
+/// `inherit (source) names;`, or `inherit names;` without a source.
+pub fn inherit_text(source: Option<&str>, names: &[String]) -> String {
+    let source = source.map(|s| format!(" {s}")).unwrap_or_default();
+    let names = names.iter().map(|name| format!(" {}", attr_text(name))).collect::<String>();
+    format!("inherit{source}{names};")
+}
+
+pub fn attrpath_keys(attrpath: &syntax::ast::Attrpath) -> Vec<String> {
+    attrpath.attrs().map(|attr| attr_key(&attr)).collect()
+}
@@ -0,0 +1,378 @@
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, HasBindings};
+
+use crate::ast::{attr_key, attr_text, attrpath_keys, line_indent};
+use crate::comments::{attached_comments, with_copied_comments, without_attached};
+use crate::edit::{with_binding, with_replaced_bindings};
+use crate::error::EditError;
+use crate::path::{path_from_root, resolve_path};
+use crate::resolve::is_rec;
+
+fn plain_set(expr: Option<syntax::ast::Expr>) -> Option<syntax::ast::AttrSet> {
+    match expr? {
+        syntax::ast::Expr::AttrSet(set) if !is_rec(set.syntax()) => Some(set),
+        _ => None,
+    }
+}
+
+/// The attribute set a file is made of, looking through the function header of
+/// modules and surrounding `let`, `with` and parentheses.
+pub fn document_set(root: &SyntaxNode) -> Option<syntax::ast::AttrSet> {
+    let mut expr = syntax::ast::SourceFile::cast(root.clone())?.expr()?;
+    loop {
+        expr = match expr {
+            syntax::ast::Expr::AttrSet(set) => return Some(set),
+            syntax::ast::Expr::Lambda(lambda) => lambda.body()?,
+            syntax::ast::Expr::LetIn(let_in) => let_in.body()?,
+            syntax::ast::Expr::With(with) => with.body()?,
+            syntax::ast::Expr::Paren(paren) => paren.expr()?,
+            _ => return None,
+        };
+    }
+}
+
+pub fn can_expand(binding: &syntax::ast::AttrpathValue) -> bool {
+    binding.attrpath().is_some_and(|ap| ap.attrs().count() > 1)
+}
+
+// `first = { ...; }` next to `binding`, which an expanded binding is merged into
+// because Nix rejects defining the same attribute twice.
+fn merge_target(binding: &syntax::ast::AttrpathValue, first: &str) -> Option<syntax::ast::AttrSet> {
+    binding.syntax().parent()?
+        .children()
+        .filter(|sibling| sibling != binding.syntax())
+        .filter_map(syntax::ast::AttrpathValue::cast)
+        .find(|sibling| sibling.attrpath().is_some_and(|ap| attrpath_keys(&ap) == [first]))
+        .and_then(|sibling| plain_set(sibling.value()))
+}
+
+/// Turns `a.b.c = v;` into `a = { b.c = v; };`, returning the new root.
+pub fn expand(binding: &syntax::ast::AttrpathValue) -> Result<SyntaxNode, EditError> {
+    let attrs = binding.attrpath()
+        .ok_or(EditError::NotApplicable("the binding has no attribute path"))?
+        .attrs()
+        .collect::<Vec<_>>();
+    if attrs.len() < 2 {
+        return Err(EditError::NotApplicable("the attribute path has a single key"));
+    }
+    let node = binding.syntax();
+    let text = node.text().to_string();
+    let rest_offset = usize::from(attrs[1].syntax().text_range().start() - node.text_range().start());
+    let rest = &text[rest_offset..];
+
+    if let Some(target) = merge_target(binding, &attr_key(&attrs[0])) {
+        // Inserting into the sibling doesn't move the binding itself.
+        let moved_away = || EditError::NotApplicable("the binding moved while merging");
+        let path = path_from_root(node);
+        let target_path = path_from_root(target.syntax());
+        let new_root = with_binding(target.syntax(), rest)?;
+        // The comments of the binding go along with it.
+        let inserted = resolve_path(&new_root, &target_path)
+            .and_then(|target| target.children().filter(|c| c.kind() == SyntaxKind::ATTR_PATH_VALUE).last())
+            .ok_or_else(moved_away)?;
+        let comments = attached_comments(node);
+        let new_root = if comments.is_empty() {
+            new_root
+        } else {
+            with_copied_comments(&inserted, &comments)?
+        };
+        return without_attached(&resolve_path(&new_root, &path).ok_or_else(moved_away)?);
+    }
+
+    let first = attrs[0].syntax().text().to_string();
+    let indent = line_indent(node);
+    with_replaced_bindings(node, &format!("{first} = {{\n{indent}  {rest}\n{indent}}};"))
+}
+
+pub fn can_collapse(binding: &syntax::ast::AttrpathValue) -> bool {
+    plain_set(binding.value()).is_some_and(|set| {
+        let bindings = set.bindings().collect::<Vec<_>>();
+        bindings.len() == 1 && matches!(bindings[0], syntax::ast::Binding::AttrpathValue(_))
+    })
+}
+
+/// Turns `a = { b = v; };` into `a.b = v;`, keeping comments of the removed set
+/// in front of the binding. Returns the new root.
+pub fn collapse(binding: &syntax::ast::AttrpathValue) -> Result<SyntaxNode, EditError> {
+    let not_applicable = || EditError::NotApplicable("the value is not a set with a single binding");
+    if !can_collapse(binding) {
+        return Err(not_applicable());
+    }
+    let set = plain_set(binding.value()).ok_or_else(not_applicable)?;
+    let inner = set.syntax().children().find(|c| c.kind() == SyntaxKind::ATTR_PATH_VALUE).ok_or_else(not_applicable)?;
+    let outer = binding.attrpath().ok_or_else(not_applicable)?.syntax().text().to_string();
+    let indent = line_indent(binding.syntax());
+    let comments = binding.syntax().children_with_tokens()
+        .chain(set.syntax().children_with_tokens())
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == SyntaxKind::COMMENT)
+        .map(|token| format!("{}\n{indent}", token.text()))
+        .collect::<String>();
+    with_replaced_bindings(binding.syntax(), &format!("{comments}{outer}.{}", inner.text()))
+}
+
+/// The attrpath as shown for editing: plain keys are written bare unless that
+/// would be ambiguous, interpolated keys keep their Nix syntax.
+pub fn display_attrpath(attrpath: &syntax::ast::Attrpath) -> String {
+    attrpath.attrs()
+        .map(|attr| match attr {
+            syntax::ast::Attr::Dynamic(_) => attr.syntax().text().to_string(),
+            _ => {
+                let key = attr_key(&attr);
+                let written = attr.syntax().text().to_string();
+                if key == written || !(key.contains('.') || key.contains('"') || key.contains("${")) {
+                    key
+                } else {
+                    written
+                }
+            },
+        })
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+/// Turns user input back into attrpath syntax. Dots separate segments except
+/// inside quotes and `${ }`; quoted and interpolated segments are kept as
+/// written, everything else is quoted and escaped where necessary.
+pub fn parse_attrpath_input(input: &str) -> String {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut in_string = false;
+    let mut depth = 0;
+    let mut chars = input.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                current.push(c);
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+                continue;
+            },
+            '"' if depth == 0 => in_string = !in_string,
+            '$' if chars.peek() == Some(&'{') => {
+                current.push(c);
+                current.push(chars.next().unwrap_or('{'));
+                depth += 1;
+                continue;
+            },
+            '}' if depth > 0 => depth -= 1,
+            '.' if !in_string && depth == 0 => {
+                segments.push(std::mem::take(&mut current));
+                continue;
+            },
+            _ => {},
+        }
+        current.push(c);
+    }
+    segments.push(current);
+    segments.iter()
+        .map(|segment| {
+            let segment = segment.trim();
+            let quoted = segment.len() > 1 && segment.starts_with('"') && segment.ends_with('"');
+            let dynamic = segment.starts_with("${") && segment.ends_with('}');
+            if quoted || dynamic {
+                segment.to_string()
+            } else {
+                attr_text(segment)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+/// An attribute of the merged view, where `a.b = x;` and `a = { b = x; };`
+/// both become a group `a` containing `b`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogicalNode {
+    pub name: String,
+    pub value: Option<SyntaxNode>,
+    pub children: Vec<LogicalNode>,
+}
+
+pub fn logical_tree(set: &syntax::ast::AttrSet) -> Vec<LogicalNode> {
+    let mut nodes = Vec::new();
+    merge_set(&mut nodes, set);
+    nodes
+}
+
+pub fn has_nesting(set: &syntax::ast::AttrSet) -> bool {
+    set.bindings().any(|binding| match binding {
+        syntax::ast::Binding::AttrpathValue(attr) => {
+            can_expand(&attr) || plain_set(attr.value()).is_some()
+        },
+        _ => false,
+    })
+}
+
+fn merge_set(nodes: &mut Vec<LogicalNode>, set: &syntax::ast::AttrSet) {
+    for binding in set.bindings() {
+        let syntax::ast::Binding::AttrpathValue(attr) = binding else {
+            continue;
+        };
+        let (Some(attrpath), Some(value)) = (attr.attrpath(), attr.value()) else {
+            continue;
+        };
+        let keys = attrpath_keys(&attrpath);
+        let Some((last, init)) = keys.split_last() else {
+            continue;
+        };
+        let mut level = &mut *nodes;
+        for key in init {
+            level = &mut group(level, key).children;
+        }
+        match plain_set(Some(value.clone())) {
+            Some(nested) => merge_set(&mut group(level, last).children, &nested),
+            None => level.push(LogicalNode {
+                name: last.clone(),
+                value: Some(value.syntax().clone()),
+                children: Vec::new(),
+            }),
+        }
+    }
+}
+
+fn group<'a>(nodes: &'a mut Vec<LogicalNode>, name: &str) -> &'a mut LogicalNode {
+    let idx = match nodes.iter().position(|n| n.name == name && n.value.is_none()) {
+        Some(idx) => idx,
+        None => {
+            nodes.push(LogicalNode {
+                name: name.to_string(),
+                value: None,
+                children: Vec::new(),
+            });
+            nodes.len() - 1
+        },
+    };
+    &mut nodes[idx]
+}
+
+fn collect_bindings(set: &SyntaxNode, prefix: &[String], target: &[String], found: &mut Vec<SyntaxNode>) {
+    let Some(set) = syntax::ast::AttrSet::cast(set.clone()) else {
+        return;
+    };
+    for binding in set.bindings() {
+        let syntax::ast::Binding::AttrpathValue(attr) = binding else {
+            continue;
+        };
+        let Some(attrpath) = attr.attrpath() else {
+            continue;
+        };
+        let mut full = prefix.to_vec();
+        full.extend(attrpath_keys(&attrpath));
+        if full.starts_with(target) {
+            found.push(attr.syntax().clone());
+        } else if target.starts_with(&full) {
+            if let Some(value) = plain_set(attr.value()) {
+                collect_bindings(value.syntax(), &full, target, found);
+            }
+        }
+    }
+}
+
+/// Every binding that defines something below `path`, however the attribute
+/// paths are split between nested sets.
+pub fn bindings_below(set: &syntax::ast::AttrSet, path: &[String]) -> Vec<SyntaxNode> {
+    let mut found = Vec::new();
+    collect_bindings(set.syntax(), &[], path, &mut found);
+    found
+}
+
+/// The node of the merged view at `path`.
+pub fn find_logical<'a>(nodes: &'a [LogicalNode], path: &[String]) -> Option<&'a LogicalNode> {
+    let (first, rest) = path.split_first()?;
+    let node = nodes.iter().find(|node| &node.name == first)?;
+    if rest.is_empty() {
+        Some(node)
+    } else {
+        find_logical(&node.children, rest)
+    }
+}
+
+/// The most deeply nested set a binding for `path` can be added to, and how
+/// many keys of `path` that set already stands for.
+pub fn insertion_point(set: &syntax::ast::AttrSet, path: &[String]) -> (syntax::ast::AttrSet, usize) {
+    let nested = set.bindings().find_map(|binding| {
+        let syntax::ast::Binding::AttrpathValue(attr) = binding else {
+            return None;
+        };
+        let keys = attrpath_keys(&attr.attrpath()?);
+        let value = plain_set(attr.value())?;
+        (keys.len() < path.len() && path.starts_with(&keys)).then_some((value, keys.len()))
+    });
+    match nested {
+        Some((value, consumed)) => {
+            let (target, deeper) = insertion_point(&value, &path[consumed..]);
+            (target, consumed + deeper)
+        },
+        None => (set.clone(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::serialize_document;
+    use crate::edit::parse;
+
+    fn binding(root: &SyntaxNode, index: usize) -> syntax::ast::AttrpathValue {
+        root.descendants().filter_map(syntax::ast::AttrpathValue::cast).nth(index).unwrap()
+    }
+
+    #[test]
+    fn expands_into_a_new_set() {
+        let root = parse("{\n  a.b.c = 1;\n}").unwrap();
+        assert_eq!(expand(&binding(&root, 0)).unwrap().to_string(), "{\n  a = {\n    b.c = 1;\n  };\n}");
+    }
+
+    #[test]
+    fn expands_into_an_existing_set() {
+        let root = parse("{\n  a.b = 1;\n  a = {\n    c = 2;\n  };\n}").unwrap();
+        assert_eq!(expand(&binding(&root, 0)).unwrap().to_string(), "{\n  a = {\n    c = 2;\n    b = 1;\n  };\n}");
+    }
+
+    #[test]
+    fn expands_into_an_existing_set_with_comments() {
+        let root = parse("{\n  # keep\n  a.b = 1; # why\n  a = {\n    c = 2;\n  };\n}").unwrap();
+        assert_eq!(
+            expand(&binding(&root, 0)).unwrap().to_string(),
+            "{\n  a = {\n    c = 2;\n    # keep\n    # why\n    b = 1;\n  };\n}",
+        );
+    }
+
+    #[test]
+    fn expands_only_attrpaths() {
+        let root = parse("{ a = 1; }").unwrap();
+        assert!(!can_expand(&binding(&root, 0)));
+        assert!(matches!(expand(&binding(&root, 0)), Err(EditError::NotApplicable(_))));
+    }
+
+    #[test]
+    fn collapses_keeping_comments() {
+        let root = parse("{\n  a = {\n    # note\n    b = 1;\n  };\n}").unwrap();
+        assert_eq!(collapse(&binding(&root, 0)).unwrap().to_string(), "{\n  # note\n  a.b = 1;\n}");
+
+        let root = parse("{ a = { b = 1; c = 2; }; }").unwrap();
+        assert!(!can_collapse(&binding(&root, 0)));
+        assert!(matches!(collapse(&binding(&root, 0)), Err(EditError::NotApplicable(_))));
+    }
+
+    #[test]
+    fn round_trips_keep_comments_and_formatting() {
+        let source = "# Machine configuration\n{\n  # note\n  a.b = 1; # trailing\n}\n";
+        let root = parse(source).unwrap();
+        assert_eq!(serialize_document(&root), source);
+
+        let expanded = expand(&binding(&root, 0)).unwrap();
+        assert_eq!(expanded.to_string(), "# Machine configuration\n{\n  # note\n  a = {\n    b = 1;\n  }; # trailing\n}\n");
+        assert_eq!(serialize_document(&collapse(&binding(&expanded, 0)).unwrap()), source);
+    }
+
+    #[test]
+    fn parses_attrpath_input() {
+        assert_eq!(parse_attrpath_input("a.b"), "a.b");
+        assert_eq!(parse_attrpath_input(" a.\"b.c\".d "), "a.\"b.c\".d");
+        assert_eq!(parse_attrpath_input("a.${name}.c"), "a.${name}.c");
+        assert_eq!(parse_attrpath_input("my key.x"), "\"my key\".x");
+    }
+}
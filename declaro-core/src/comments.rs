@@ -0,0 +1,193 @@
+use std::ops::RangeInclusive;
+
+use rowan::NodeOrToken;
+use syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+use crate::ast::{child_index, is_blank, line_indent};
+use crate::edit::splice_children;
+use crate::error::EditError;
+
+/// The text of a comment without `#` or `/* */`.
+pub fn comment_text(token: &SyntaxToken) -> String {
+    let text = token.text();
+    match text.strip_prefix("/*").and_then(|t| t.strip_suffix("*/")) {
+        Some(inner) => inner.trim().to_string(),
+        None => text.trim_start_matches('#').trim().to_string(),
+    }
+}
+
+pub fn is_block_comment(token: &SyntaxToken) -> bool {
+    token.text().starts_with("/*")
+}
+
+fn comment_syntax(text: &str, block: bool) -> String {
+    if block || text.contains('\n') {
+        format!("/* {} */", text.replace("*/", "* /"))
+    } else {
+        format!("# {text}")
+    }
+}
+
+// A comment on the same line as the node before it belongs to that node.
+fn is_trailing(comment: &SyntaxToken) -> bool {
+    let mut element = comment.prev_sibling_or_token();
+    while let Some(NodeOrToken::Token(token)) = &element {
+        if !is_blank(token) || token.text().contains('\n') {
+            return false;
+        }
+        element = token.prev_sibling_or_token();
+    }
+    element.is_some()
+}
+
+/// The comments directly above `node`, up to the first blank line.
+pub fn leading_comments(node: &SyntaxNode) -> Vec<SyntaxToken> {
+    let mut comments = Vec::new();
+    let mut element = node.prev_sibling_or_token();
+    while let Some(NodeOrToken::Token(token)) = element {
+        if token.kind() == SyntaxKind::COMMENT {
+            if is_trailing(&token) {
+                break;
+            }
+            comments.push(token.clone());
+        } else if !is_blank(&token) || token.text().matches('\n').count() > 1 {
+            break;
+        }
+        element = token.prev_sibling_or_token();
+    }
+    comments.reverse();
+    comments
+}
+
+/// The comment on the same line after `node`, skipping a separating comma.
+pub fn trailing_comment(node: &SyntaxNode) -> Option<SyntaxToken> {
+    let mut element = node.next_sibling_or_token();
+    while let Some(NodeOrToken::Token(token)) = element {
+        match token.kind() {
+            SyntaxKind::COMMENT => return Some(token),
+            _ if is_blank(&token) && !token.text().contains('\n') => {},
+            _ if token.text() == "," => {},
+            _ => return None,
+        }
+        element = token.next_sibling_or_token();
+    }
+    None
+}
+
+fn trivia_tokens(text: &str) -> Vec<rowan::GreenToken> {
+    syntax::parse_file(text).syntax_node()
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .map(|token| token.green().to_owned())
+        .collect()
+}
+
+/// The new root after changing the text of `comment`. An empty text removes
+/// the comment together with the whitespace that separated it.
+pub fn with_comment_text(comment: &SyntaxToken, text: &str) -> SyntaxNode {
+    let parent = comment.parent().expect("comment must have parent");
+    let idx = comment.index();
+    if text.trim().is_empty() {
+        let elements = parent.children_with_tokens().collect::<Vec<_>>();
+        let blank = |i: usize| elements.get(i)
+            .and_then(|element| element.as_token())
+            .is_some_and(is_blank);
+        let range = if is_trailing(comment) && idx > 0 && blank(idx - 1) {
+            idx - 1..=idx
+        } else if blank(idx + 1) {
+            idx..=idx + 1
+        } else {
+            idx..=idx
+        };
+        return SyntaxNode::new_root(splice_children(&parent, |children| {
+            children.drain(range);
+        }));
+    }
+    let replacement = trivia_tokens(&comment_syntax(text.trim(), is_block_comment(comment)));
+    SyntaxNode::new_root(splice_children(&parent, |children| {
+        children.splice(idx..=idx, replacement.into_iter().take(1).map(NodeOrToken::Token));
+    }))
+}
+
+/// The new root after adding a comment on its own line above `node`.
+pub fn with_leading_comment(node: &SyntaxNode, text: &str) -> Result<SyntaxNode, EditError> {
+    let parent = node.parent().ok_or(EditError::Root)?;
+    let idx = child_index(&parent, node);
+    let indent = line_indent(node);
+    let inserted = trivia_tokens(&format!("{}\n{indent}", comment_syntax(text.trim(), false)));
+    Ok(SyntaxNode::new_root(splice_children(&parent, |children| {
+        children.splice(idx..idx, inserted.into_iter().map(NodeOrToken::Token));
+    })))
+}
+
+/// The new root after putting copies of `comments` on their own lines above
+/// `node`, e.g. when the node they belonged to moved there.
+pub fn with_copied_comments(node: &SyntaxNode, comments: &[SyntaxToken]) -> Result<SyntaxNode, EditError> {
+    let parent = node.parent().ok_or(EditError::Root)?;
+    let idx = child_index(&parent, node);
+    let indent = line_indent(node);
+    let text = comments.iter().map(|comment| format!("{}\n{indent}", comment.text())).collect::<String>();
+    let inserted = trivia_tokens(&text);
+    Ok(SyntaxNode::new_root(splice_children(&parent, |children| {
+        children.splice(idx..idx, inserted.into_iter().map(NodeOrToken::Token));
+    })))
+}
+
+// The children of `node`'s parent that move together with it: its leading
+// comments, the node and its trailing comment.
+fn attached_range(node: &SyntaxNode) -> RangeInclusive<usize> {
+    let start = leading_comments(node).first()
+        .map(|comment| comment.index())
+        .unwrap_or_else(|| node.index());
+    let end = trailing_comment(node)
+        .filter(|comment| !comment.text().contains('\n'))
+        .map(|comment| comment.index())
+        .unwrap_or_else(|| node.index());
+    start..=end
+}
+
+/// The comments that belong to `node`: those above it and the one after it
+/// on the same line.
+pub fn attached_comments(node: &SyntaxNode) -> Vec<SyntaxToken> {
+    let mut comments = leading_comments(node);
+    comments.extend(trailing_comment(node).filter(|comment| !comment.text().contains('\n')));
+    comments
+}
+
+/// The new root after removing `node` together with its attached comments
+/// and the whitespace before them.
+pub fn without_attached(node: &SyntaxNode) -> Result<SyntaxNode, EditError> {
+    let parent = node.parent().ok_or(EditError::Root)?;
+    let range = attached_range(node);
+    let start = match parent.children_with_tokens().nth(range.start().wrapping_sub(1)) {
+        Some(NodeOrToken::Token(token)) if is_blank(&token) => range.start() - 1,
+        _ => *range.start(),
+    };
+    Ok(SyntaxNode::new_root(splice_children(&parent, |children| {
+        children.drain(start..=*range.end());
+    })))
+}
+
+/// The new root after swapping `node` with its previous (`up`) or next sibling
+/// node, keeping the comments attached to both.
+pub fn with_moved(node: &SyntaxNode, up: bool) -> Result<SyntaxNode, EditError> {
+    let parent = node.parent().ok_or(EditError::Root)?;
+    let sibling = if up { node.prev_sibling() } else { node.next_sibling() }
+        .ok_or(EditError::NotApplicable("there is no sibling to swap with"))?;
+    let (first, second) = if up { (sibling, node.clone()) } else { (node.clone(), sibling) };
+    let first = attached_range(&first);
+    let second = attached_range(&second);
+    if first.end() >= second.start() {
+        return Err(EditError::NotApplicable("the comments of both nodes overlap"));
+    }
+    Ok(SyntaxNode::new_root(splice_children(&parent, |children| {
+        let tail = children.split_off(second.end() + 1);
+        let moved_second = children.split_off(*second.start());
+        let between = children.split_off(first.end() + 1);
+        let moved_first = children.split_off(*first.start());
+        children.extend(moved_second);
+        children.extend(between);
+        children.extend(moved_first);
+        children.extend(tail);
+    })))
+}
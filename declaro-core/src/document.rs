@@ -0,0 +1,387 @@
+use serde_json::{Map, Value};
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, HasStringParts};
+
+use crate::ast::{attr_text, attrpath_keys};
+use crate::attrpath::{
+    bindings_below, document_set, find_logical, insertion_point, logical_tree, parse_attrpath_input,
+    LogicalNode,
+};
+use crate::edit::{parse, source_expr, with_binding, with_node_value, without_nodes};
+use crate::error::EditError;
+
+/// A parsed Nix file addressed by attribute paths, like
+/// `services.openssh.enable`. Edits return a new document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Document {
+    root: SyntaxNode,
+}
+
+/// The keys of an attribute path as typed by a user, e.g. `a."b.c".d`.
+pub fn parse_attrpath(input: &str) -> Result<Vec<String>, EditError> {
+    let root = parse(&format!("{{ {} = null; }}", parse_attrpath_input(input)))?;
+    root.descendants()
+        .find_map(syntax::ast::Attrpath::cast)
+        .map(|attrpath| attrpath_keys(&attrpath))
+        .ok_or(EditError::UnexpectedSyntax("an attribute path"))
+}
+
+fn missing(path: &[String]) -> EditError {
+    EditError::Missing(path.join("."))
+}
+
+impl Document {
+    pub fn parse(source: &str) -> Result<Document, EditError> {
+        parse(source).map(Document::from_root)
+    }
+
+    pub fn from_root(root: SyntaxNode) -> Document {
+        Document { root }
+    }
+
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    /// The full source, including comments around the expression.
+    pub fn text(&self) -> String {
+        self.root.to_string()
+    }
+
+    fn set_node(&self) -> Result<syntax::ast::AttrSet, EditError> {
+        document_set(&self.root).ok_or(EditError::NotApplicable("the document is not an attribute set"))
+    }
+
+    /// The attribute at `path`, which is a group when its children are
+    /// defined by several bindings.
+    pub fn get(&self, path: &[String]) -> Result<LogicalNode, EditError> {
+        let tree = logical_tree(&self.set_node()?);
+        find_logical(&tree, path).cloned().ok_or_else(|| missing(path))
+    }
+
+    pub fn get_json(&self, path: &[String]) -> Result<Value, EditError> {
+        self.get(path).map(|node| logical_json(&node))
+    }
+
+    /// The Nix source of the value at `path`, or of all bindings below it.
+    pub fn get_source(&self, path: &[String]) -> Result<String, EditError> {
+        Ok(match self.get(path)?.value {
+            Some(value) => value.text().to_string(),
+            None => bindings_below(&self.set_node()?, path).iter()
+                .map(|binding| binding.text().to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        })
+    }
+
+    /// Sets `path` to the Nix expression `value`. An existing value is replaced
+    /// in place; otherwise the bindings below `path` are replaced by a new one
+    /// in the most deeply nested set that already exists.
+    pub fn set(&self, path: &[String], value: &str) -> Result<Document, EditError> {
+        let value_root = parse(value)?;
+        if source_expr(&value_root).is_none() {
+            return Err(EditError::UnexpectedSyntax("an expression"));
+        }
+        let set = self.set_node()?;
+        let tree = logical_tree(&set);
+        if let Some(old) = find_logical(&tree, path).and_then(|node| node.value.as_ref()) {
+            return with_node_value(old, value, source_expr).map(Document::from_root);
+        }
+        // Adding `a.b` next to `a = foo;` would define `a` twice.
+        let bound = (1..path.len())
+            .map(|len| &path[..len])
+            .find(|prefix| find_logical(&tree, prefix).is_some_and(|node| node.value.is_some()));
+        if let Some(prefix) = bound {
+            return Err(EditError::NotASet(prefix.join(".")));
+        }
+        let stale = bindings_below(&set, path);
+        let cleared = Document::from_root(without_nodes(&self.root, &stale)?);
+        let (target, consumed) = insertion_point(&cleared.set_node()?, path);
+        let attrpath = path[consumed..].iter()
+            .map(|key| attr_text(key))
+            .collect::<Vec<String>>()
+            .join(".");
+        with_binding(target.syntax(), &format!("{attrpath} = {};", value.trim())).map(Document::from_root)
+    }
+
+    /// Removes every binding that defines something below `path`.
+    pub fn delete(&self, path: &[String]) -> Result<Document, EditError> {
+        let bindings = bindings_below(&self.set_node()?, path);
+        if bindings.is_empty() {
+            return Err(missing(path));
+        }
+        without_nodes(&self.root, &bindings).map(Document::from_root)
+    }
+
+    /// The paths of all values below `path`, written as attribute paths.
+    pub fn list(&self, path: &[String]) -> Result<Vec<String>, EditError> {
+        let mut lines = Vec::new();
+        match path.split_last() {
+            Some((_, parents)) => {
+                let prefix = parents.iter().map(|key| attr_text(key)).collect::<Vec<String>>();
+                leaf_paths(&self.get(path)?, &prefix, &mut lines);
+            },
+            None => logical_tree(&self.set_node()?).iter().for_each(|node| leaf_paths(node, &[], &mut lines)),
+        }
+        Ok(lines)
+    }
+}
+
+fn leaf_paths(node: &LogicalNode, prefix: &[String], lines: &mut Vec<String>) {
+    let mut path = prefix.to_vec();
+    path.push(attr_text(&node.name));
+    if node.value.is_some() {
+        lines.push(path.join("."));
+    }
+    for child in &node.children {
+        leaf_paths(child, &path, lines);
+    }
+}
+
+fn logical_json(node: &LogicalNode) -> Value {
+    match &node.value {
+        Some(value) => to_json(value),
+        None => Value::Object(node.children.iter()
+            .map(|child| (child.name.clone(), logical_json(child)))
+            .collect::<Map<String, Value>>()),
+    }
+}
+
+fn unescape(escape: &str) -> String {
+    match escape.trim_start_matches('\\') {
+        "n" => "\n".to_string(),
+        "r" => "\r".to_string(),
+        "t" => "\t".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn string_json(node: &SyntaxNode) -> Option<Value> {
+    let string = syntax::ast::String::cast(node.clone())?;
+    string.string_parts()
+        .map(|part| match part {
+            syntax::ast::StringPart::Fragment(text) => Some(text.text().to_string()),
+            syntax::ast::StringPart::Escape(escape) => Some(unescape(escape.text())),
+            syntax::ast::StringPart::Dynamic(_) => None,
+        })
+        .collect::<Option<String>>()
+        .map(Value::String)
+}
+
+// Escapes of indented strings: `''$` and `'''` stand for `$` and `''`, and
+// `''\n` for the escape `\n` of a normal string.
+fn indent_unescape(escape: &str) -> String {
+    match escape.strip_prefix("''") {
+        Some("'") => "''".to_string(),
+        Some(rest) => unescape(rest),
+        None => escape.to_string(),
+    }
+}
+
+enum IndentPiece {
+    Text(String),
+    Escaped(String),
+}
+
+// The spaces a line starts with, and whether anything else follows them.
+// Escaped characters end the indentation.
+fn leading_spaces(line: &[IndentPiece]) -> (usize, bool) {
+    let mut count = 0;
+    for piece in line {
+        match piece {
+            IndentPiece::Text(text) => {
+                let spaces = text.len() - text.trim_start_matches(' ').len();
+                count += spaces;
+                if spaces < text.len() {
+                    return (count, true);
+                }
+            },
+            IndentPiece::Escaped(_) => return (count, true),
+        }
+    }
+    (count, false)
+}
+
+// Removes the indentation all lines share, like Nix does: a first line of
+// only spaces is dropped, as is the last one, and lines of only spaces don't
+// count towards the shared indentation.
+fn indent_string_json(node: &SyntaxNode) -> Option<Value> {
+    let string = syntax::ast::IndentString::cast(node.clone())?;
+    let mut lines = vec![Vec::new()];
+    for part in string.string_parts() {
+        match part {
+            syntax::ast::StringPart::Fragment(text) => {
+                let mut segments = text.text().split('\n');
+                if let Some(first) = segments.next() {
+                    lines.last_mut()?.push(IndentPiece::Text(first.to_string()));
+                }
+                for segment in segments {
+                    lines.push(vec![IndentPiece::Text(segment.to_string())]);
+                }
+            },
+            syntax::ast::StringPart::Escape(escape) => {
+                lines.last_mut()?.push(IndentPiece::Escaped(indent_unescape(escape.text())));
+            },
+            syntax::ast::StringPart::Dynamic(_) => return None,
+        }
+    }
+    if lines.len() > 1 && !leading_spaces(&lines[0]).1 {
+        lines.remove(0);
+    }
+    if lines.last().is_some_and(|line| !leading_spaces(line).1) {
+        lines.last_mut()?.clear();
+    }
+    let indent = lines.iter()
+        .map(|line| leading_spaces(line))
+        .filter(|(_, content)| *content)
+        .map(|(spaces, _)| spaces)
+        .min()
+        .unwrap_or(0);
+    let text = lines.into_iter()
+        .map(|line| {
+            let mut strip = indent;
+            line.into_iter()
+                .map(|piece| match piece {
+                    IndentPiece::Text(text) => {
+                        let spaces = (text.len() - text.trim_start_matches(' ').len()).min(strip);
+                        strip -= spaces;
+                        text[spaces..].to_string()
+                    },
+                    IndentPiece::Escaped(text) => {
+                        strip = 0;
+                        text
+                    },
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    Some(Value::String(text))
+}
+
+/// The value as JSON. Expressions without a JSON counterpart, such as
+/// functions, references or interpolated strings, become their Nix source.
+pub fn to_json(node: &SyntaxNode) -> Value {
+    let source = || Value::String(node.text().to_string());
+    match node.kind() {
+        SyntaxKind::PAREN => node.children().next().map(|inner| to_json(&inner)).unwrap_or_else(source),
+        SyntaxKind::STRING => string_json(node).unwrap_or_else(source),
+        SyntaxKind::INDENT_STRING => indent_string_json(node).unwrap_or_else(source),
+        SyntaxKind::LITERAL => {
+            let text = node.text().to_string();
+            match syntax::ast::Literal::cast(node.clone()).and_then(|literal| literal.kind()) {
+                Some(syntax::ast::LiteralKind::Int) => text.parse::<i64>().map(Value::from).unwrap_or_else(|_| source()),
+                Some(syntax::ast::LiteralKind::Float) => text.parse::<f64>().map(Value::from).unwrap_or_else(|_| source()),
+                _ => Value::String(text),
+            }
+        },
+        SyntaxKind::REF => match node.text().to_string().as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "null" => Value::Null,
+            _ => source(),
+        },
+        SyntaxKind::LIST => Value::Array(node.children().map(|element| to_json(&element)).collect()),
+        SyntaxKind::ATTR_SET => match syntax::ast::AttrSet::cast(node.clone()) {
+            Some(set) => Value::Object(logical_tree(&set).iter()
+                .map(|child| (child.name.clone(), logical_json(child)))
+                .collect::<Map<String, Value>>()),
+            None => source(),
+        },
+        _ => source(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const CONFIG: &str = "# Machine configuration
+{ pkgs, ... }:
+{
+  # Remote access
+  services.openssh.enable = false;
+  networking = {
+    hostName = \"box\";
+  };
+}
+";
+
+    fn path(input: &str) -> Vec<String> {
+        parse_attrpath(input).unwrap()
+    }
+
+    #[test]
+    fn parses_quoted_attrpaths() {
+        assert_eq!(path("a.\"b.c\".d"), ["a", "b.c", "d"]);
+    }
+
+    #[test]
+    fn gets_values_however_the_paths_are_split() {
+        let document = Document::parse(CONFIG).unwrap();
+        assert_eq!(document.get_json(&path("networking.hostName")).unwrap(), json!("box"));
+        assert_eq!(document.get_json(&path("services")).unwrap(), json!({ "openssh": { "enable": false } }));
+        assert_eq!(document.get_source(&path("services.openssh.enable")).unwrap(), "false");
+        assert_eq!(document.get(&path("services.nginx")), Err(EditError::Missing("services.nginx".to_string())));
+    }
+
+    #[test]
+    fn sets_existing_values_in_place() {
+        let document = Document::parse(CONFIG).unwrap().set(&path("services.openssh.enable"), "true").unwrap();
+        assert_eq!(document.text(), CONFIG.replace("enable = false", "enable = true"));
+    }
+
+    #[test]
+    fn adds_new_values_to_the_deepest_existing_set() {
+        let document = Document::parse("{\n  a = 1;\n}\n").unwrap().set(&path("b"), "2").unwrap();
+        assert_eq!(document.text(), "{\n  a = 1;\n  b = 2;\n}\n");
+
+        let document = Document::parse(CONFIG).unwrap().set(&path("networking.firewall.enable"), "false").unwrap();
+        assert_eq!(document.get_json(&path("networking")).unwrap(), json!({
+            "hostName": "box",
+            "firewall": { "enable": false },
+        }));
+        assert!(document.text().starts_with("# Machine configuration\n"));
+        assert!(document.text().contains("# Remote access"));
+    }
+
+    #[test]
+    fn refuses_to_set_below_values_that_are_not_sets() {
+        let document = Document::parse("{ a = foo; }").unwrap();
+        assert_eq!(document.set(&path("a.b"), "1"), Err(EditError::NotASet("a".to_string())));
+    }
+
+    #[test]
+    fn refuses_invalid_values() {
+        let document = Document::parse(CONFIG).unwrap();
+        assert!(matches!(document.set(&path("a"), "{"), Err(EditError::Parse(_))));
+    }
+
+    #[test]
+    fn deletes_everything_below_a_path() {
+        let document = Document::parse(CONFIG).unwrap().delete(&path("services")).unwrap();
+        assert_eq!(document.get(&path("services.openssh.enable")), Err(EditError::Missing("services.openssh.enable".to_string())));
+        assert_eq!(document.list(&[]).unwrap(), ["networking.hostName"]);
+        assert!(document.text().starts_with("# Machine configuration\n"));
+        assert_eq!(
+            Document::parse(CONFIG).unwrap().delete(&path("boot")),
+            Err(EditError::Missing("boot".to_string())),
+        );
+    }
+
+    #[test]
+    fn lists_leaf_paths() {
+        let document = Document::parse(CONFIG).unwrap();
+        assert_eq!(document.list(&[]).unwrap(), ["services.openssh.enable", "networking.hostName"]);
+        assert_eq!(document.list(&path("networking")).unwrap(), ["networking.hostName"]);
+    }
+
+    #[test]
+    fn converts_indented_strings() {
+        let document = Document::parse("{\n  text = ''\n    hello\n      world\n  '';\n  escaped = ''a ''${b} '''c'''';\n}\n").unwrap();
+        assert_eq!(document.get_json(&path("text")).unwrap(), json!("hello\n  world\n"));
+        assert_eq!(document.get_json(&path("escaped")).unwrap(), json!("a ${b} ''c''"));
+    }
+}
@@ -0,0 +1,249 @@
+//! Edits of the syntax tree. Every edit takes nodes of the current tree and
+//! returns the root of the new tree, leaving the old tree untouched.
+
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::AstNode;
+
+use crate::ast::{child_index, is_blank};
+use crate::error::EditError;
+use crate::path::{path_from_root, resolve_path};
+
+type GreenElement = rowan::NodeOrToken<rowan::GreenNode, rowan::GreenToken>;
+
+/// Parses `source` as a Nix file, failing on any syntax error.
+pub fn parse(source: &str) -> Result<SyntaxNode, EditError> {
+    let parse = syntax::parse_file(source);
+    if !parse.errors().is_empty() {
+        return Err(EditError::Parse(parse.errors().iter().map(|e| e.to_string()).collect()));
+    }
+    Ok(parse.syntax_node())
+}
+
+/// The expression of a parsed file.
+pub fn source_expr(root: &SyntaxNode) -> Option<SyntaxNode> {
+    syntax::ast::SourceFile::cast(root.clone())?.expr().map(|expr| expr.syntax().clone())
+}
+
+pub fn splice_children<F>(parent: &SyntaxNode, edit: F) -> rowan::GreenNode
+where
+    F: FnOnce(&mut Vec<GreenElement>),
+{
+    let mut children: Vec<GreenElement> =
+        parent.green().children().map(|c| {
+            c.to_owned()
+        }).collect();
+    edit(&mut children);
+    let new_parent = rowan::GreenNode::new(rowan::SyntaxKind(parent.kind() as u16), children);
+    parent.replace_with(new_parent)
+}
+
+/// The new root after putting `new` in the place of `old`.
+pub fn replace_node(old: &SyntaxNode, new: &SyntaxNode) -> Result<SyntaxNode, EditError> {
+    let parent = old.parent().ok_or(EditError::Root)?;
+    let idx = child_index(&parent, old);
+    Ok(SyntaxNode::new_root(splice_children(&parent, |children| {
+        children[idx] = rowan::NodeOrToken::Node(new.green().into_owned());
+    })))
+}
+
+/// The new root after replacing `node` with the node `extract_new_node` picks
+/// from the parsed `new_value`.
+pub fn with_node_value<F>(node: &SyntaxNode, new_value: &str, extract_new_node: F) -> Result<SyntaxNode, EditError>
+where
+    F: Fn(&SyntaxNode) -> Option<SyntaxNode>,
+{
+    let new_syntax = syntax::parse_file(new_value).syntax_node();
+    let new_syntax = extract_new_node(&new_syntax).ok_or(EditError::UnexpectedSyntax("an expression"))?;
+    replace_node(node, &new_syntax)
+}
+
+/// The new root after removing `node` together with the whitespace
+/// separating it from its predecessor.
+pub fn without_node(node: &SyntaxNode) -> Result<SyntaxNode, EditError> {
+    let parent = node.parent().ok_or(EditError::Root)?;
+    let idx = child_index(&parent, node);
+    let remove_from = match parent.children_with_tokens().nth(idx.wrapping_sub(1)) {
+        Some(rowan::NodeOrToken::Token(token)) if is_blank(&token) => idx - 1,
+        _ => idx,
+    };
+    Ok(SyntaxNode::new_root(splice_children(&parent, |children| {
+        children.drain(remove_from..=idx);
+    })))
+}
+
+/// The new root after removing all of `nodes` from the tree of `root`. Later
+/// nodes are removed first so the paths of earlier ones stay valid.
+pub fn without_nodes(root: &SyntaxNode, nodes: &[SyntaxNode]) -> Result<SyntaxNode, EditError> {
+    let mut nodes = nodes.to_vec();
+    nodes.sort_by_key(|node| std::cmp::Reverse(node.text_range().start()));
+    nodes.iter()
+        .map(path_from_root)
+        .try_fold(root.clone(), |current, path| match resolve_path(&current, &path) {
+            Some(node) => without_node(&node),
+            None => Ok(current),
+        })
+}
+
+/// The new root after appending `binding` to the end of the attribute set `set`,
+/// indented like the existing bindings.
+pub fn with_binding(set: &SyntaxNode, binding: &str) -> Result<SyntaxNode, EditError> {
+    let parsed = syntax::parse_file(&format!("{{ {binding} }}")).syntax_node();
+    let new_binding = parsed.descendants()
+        .find(|n| matches!(n.kind(), SyntaxKind::ATTR_PATH_VALUE | SyntaxKind::INHERIT))
+        .ok_or(EditError::UnexpectedSyntax("a binding"))?;
+    let space = parsed.descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(is_blank)
+        .ok_or(EditError::UnexpectedSyntax("whitespace"))?;
+    let close = set.children_with_tokens()
+        .position(|c| c.as_token().is_some_and(|t| t.text() == "}"))
+        .ok_or(EditError::NotApplicable("not an attribute set"))?;
+    let indent = set.children()
+        .filter(|c| matches!(c.kind(), SyntaxKind::ATTR_PATH_VALUE | SyntaxKind::INHERIT))
+        .last()
+        .and_then(|last| last.prev_sibling_or_token())
+        .and_then(|element| element.into_token())
+        .filter(is_blank)
+        .map(|token| token.text().to_string())
+        .unwrap_or_else(|| " ".to_string());
+    let blank_before_close = set.children_with_tokens()
+        .nth(close.wrapping_sub(1))
+        .and_then(|element| element.into_token())
+        .is_some_and(|token| is_blank(&token));
+    let whitespace = |text: &str| rowan::NodeOrToken::Token(
+        rowan::GreenToken::new(space.green().kind(), text)
+    );
+    Ok(SyntaxNode::new_root(splice_children(set, |children| {
+        let mut inserted = vec![
+            whitespace(&indent),
+            rowan::NodeOrToken::Node(new_binding.green().into_owned()),
+        ];
+        if blank_before_close {
+            children.splice(close - 1..close - 1, inserted);
+        } else {
+            inserted.push(whitespace(" "));
+            children.splice(close..close, inserted);
+        }
+    })))
+}
+
+/// The new root after replacing the binding `node` with the bindings and
+/// comments in `bindings`.
+pub fn with_replaced_bindings(node: &SyntaxNode, bindings: &str) -> Result<SyntaxNode, EditError> {
+    let parsed = syntax::parse_file(&format!("{{\n{bindings}\n}}")).syntax_node();
+    let set = parsed.descendants()
+        .find(|n| n.kind() == SyntaxKind::ATTR_SET)
+        .ok_or(EditError::UnexpectedSyntax("bindings"))?;
+    let elements = set.children_with_tokens().collect::<Vec<_>>();
+    let no_bindings = || EditError::UnexpectedSyntax("bindings");
+    let open = elements.iter().position(|c| c.as_token().is_some_and(|t| t.text() == "{")).ok_or_else(no_bindings)?;
+    let close = elements.iter().rposition(|c| c.as_token().is_some_and(|t| t.text() == "}")).ok_or_else(no_bindings)?;
+    let inner = &elements[open + 1..close];
+    let first = inner.iter().position(|c| !c.as_token().is_some_and(is_blank)).ok_or_else(no_bindings)?;
+    let last = inner.iter().rposition(|c| !c.as_token().is_some_and(is_blank)).ok_or_else(no_bindings)?;
+    let replacement = inner[first..=last].iter()
+        .map(|element| match element {
+            rowan::NodeOrToken::Node(node) => rowan::NodeOrToken::Node(node.green().into_owned()),
+            rowan::NodeOrToken::Token(token) => rowan::NodeOrToken::Token(token.green().to_owned()),
+        })
+        .collect::<Vec<_>>();
+    let parent = node.parent().ok_or(EditError::Root)?;
+    let idx = child_index(&parent, node);
+    Ok(SyntaxNode::new_root(splice_children(&parent, |children| {
+        children.splice(idx..=idx, replacement);
+    })))
+}
+
+/// The new root after adding or removing the `rec` keyword of `set`.
+pub fn with_rec(set: &SyntaxNode, rec: bool) -> Result<SyntaxNode, EditError> {
+    let elements = set.children_with_tokens().collect::<Vec<_>>();
+    let keyword = elements.iter().position(|c| c.as_token().is_some_and(|t| t.text() == "rec"));
+    match (keyword, rec) {
+        (None, true) => {
+            let parsed = syntax::parse_file("rec { }").syntax_node();
+            let prefix = parsed.descendants_with_tokens()
+                .filter_map(|element| element.into_token())
+                .take_while(|token| token.text() != "{")
+                .map(|token| rowan::NodeOrToken::Token(token.green().to_owned()))
+                .collect::<Vec<_>>();
+            Ok(SyntaxNode::new_root(splice_children(set, |children| {
+                children.splice(0..0, prefix);
+            })))
+        },
+        (Some(idx), false) => {
+            let blank_after = elements.get(idx + 1)
+                .and_then(|element| element.as_token())
+                .is_some_and(is_blank);
+            let end = if blank_after { idx + 1 } else { idx };
+            Ok(SyntaxNode::new_root(splice_children(set, |children| {
+                children.drain(idx..=end);
+            })))
+        },
+        (Some(_), true) => Err(EditError::NotApplicable("the set is already recursive")),
+        (None, false) => Err(EditError::NotApplicable("the set is not recursive")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first(root: &SyntaxNode, kind: SyntaxKind) -> SyntaxNode {
+        root.descendants().find(|node| node.kind() == kind).unwrap()
+    }
+
+    fn bindings(root: &SyntaxNode) -> Vec<SyntaxNode> {
+        root.descendants().filter(|node| node.kind() == SyntaxKind::ATTR_PATH_VALUE).collect()
+    }
+
+    #[test]
+    fn rejects_syntax_errors() {
+        assert!(matches!(parse("{ a = ; }"), Err(EditError::Parse(_))));
+    }
+
+    #[test]
+    fn appends_bindings_with_the_indent_of_the_last_one() {
+        let root = parse("{\n  a = 1;\n}\n").unwrap();
+        let new_root = with_binding(&first(&root, SyntaxKind::ATTR_SET), "b = 2;").unwrap();
+        assert_eq!(new_root.to_string(), "{\n  a = 1;\n  b = 2;\n}\n");
+
+        let root = parse("{ }").unwrap();
+        let new_root = with_binding(&first(&root, SyntaxKind::ATTR_SET), "inherit a;").unwrap();
+        assert_eq!(new_root.to_string(), "{ inherit a; }");
+    }
+
+    #[test]
+    fn removes_nodes_with_their_leading_whitespace() {
+        let root = parse("{\n  a = 1;\n  b = 2;\n  c = 3;\n}").unwrap();
+        let bindings = bindings(&root);
+        let new_root = without_nodes(&root, &[bindings[0].clone(), bindings[2].clone()]).unwrap();
+        assert_eq!(new_root.to_string(), "{\n  b = 2;\n}");
+        assert_eq!(without_node(&root), Err(EditError::Root));
+    }
+
+    #[test]
+    fn toggles_rec() {
+        let root = parse("{ a = 1; }").unwrap();
+        let recursive = with_rec(&first(&root, SyntaxKind::ATTR_SET), true).unwrap();
+        assert_eq!(recursive.to_string(), "rec { a = 1; }");
+        let set = first(&recursive, SyntaxKind::ATTR_SET);
+        assert!(matches!(with_rec(&set, true), Err(EditError::NotApplicable(_))));
+        assert_eq!(with_rec(&set, false).unwrap().to_string(), "{ a = 1; }");
+        assert!(matches!(with_rec(&first(&root, SyntaxKind::ATTR_SET), false), Err(EditError::NotApplicable(_))));
+    }
+
+    #[test]
+    fn replaces_a_binding_with_several() {
+        let root = parse("{\n  a = 1;\n  d = 4;\n}").unwrap();
+        let new_root = with_replaced_bindings(&bindings(&root)[0], "# note\n  b = 2;\n  c = 3;").unwrap();
+        assert_eq!(new_root.to_string(), "{\n  # note\n  b = 2;\n  c = 3;\n  d = 4;\n}");
+    }
+
+    #[test]
+    fn replaces_values() {
+        let root = parse("# config\n{ a = 1; }\n").unwrap();
+        let literal = first(&root, SyntaxKind::LITERAL);
+        let new_root = with_node_value(&literal, "[ 1 2 ]", source_expr).unwrap();
+        assert_eq!(new_root.to_string(), "# config\n{ a = [ 1 2 ]; }\n");
+    }
+}
@@ -0,0 +1,35 @@
+/// Why an edit or lookup could not be carried out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditError {
+    /// Source text that doesn't parse, with the parser's messages.
+    Parse(Vec<String>),
+    /// Inserted text that parses but lacks the kind of node the edit needs.
+    UnexpectedSyntax(&'static str),
+    /// The root can't be removed or replaced through its parent.
+    Root,
+    /// An attribute path that is not defined.
+    Missing(String),
+    /// A prefix of an attribute path is bound to something other than an
+    /// attribute set, so nothing can be defined below it.
+    NotASet(String),
+    /// The edit doesn't apply to the node, e.g. collapsing a set with several bindings.
+    NotApplicable(&'static str),
+    /// The semantic analysis has no information about the node.
+    Analysis(String),
+}
+
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditError::Parse(errors) => write!(f, "parse error: {}", errors.join("; ")),
+            EditError::UnexpectedSyntax(expected) => write!(f, "expected {expected}"),
+            EditError::Root => write!(f, "the root node has no parent"),
+            EditError::Missing(path) => write!(f, "{path} is not defined"),
+            EditError::NotASet(path) => write!(f, "{path} is not an attribute set"),
+            EditError::NotApplicable(reason) => write!(f, "{reason}"),
+            EditError::Analysis(message) => write!(f, "analysis: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
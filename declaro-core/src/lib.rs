@@ -0,0 +1,18 @@
+//! The editing core of Declaro. Everything here works on plain `SyntaxNode`
+//! values: edits take nodes of the current tree and return the new root, and
+//! failures are reported as `EditError`, so the GUI, the command line and
+//! other tools can share it without a Dioxus runtime.
+
+pub mod ast;
+pub mod attrpath;
+pub mod comments;
+pub mod document;
+pub mod edit;
+pub mod error;
+pub mod operators;
+pub mod path;
+pub mod resolve;
+pub mod scope;
+
+pub use document::Document;
+pub use error::EditError;
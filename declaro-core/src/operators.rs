@@ -0,0 +1,156 @@
+use syntax::{SyntaxKind, SyntaxNode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+    None,
+}
+
+/// Binary operators with their precedence (lower binds tighter) and
+/// associativity, following the Nix manual.
+pub const BINARY_OPERATORS: [(&str, u8, Assoc); 16] = [
+    ("++", 5, Assoc::Right),
+    ("*", 6, Assoc::Left),
+    ("/", 6, Assoc::Left),
+    ("+", 7, Assoc::Left),
+    ("-", 7, Assoc::Left),
+    ("//", 9, Assoc::Right),
+    ("<", 10, Assoc::None),
+    ("<=", 10, Assoc::None),
+    (">", 10, Assoc::None),
+    (">=", 10, Assoc::None),
+    ("==", 11, Assoc::None),
+    ("!=", 11, Assoc::None),
+    ("&&", 12, Assoc::Left),
+    ("||", 13, Assoc::Left),
+    ("->", 14, Assoc::Right),
+    ("|>", 15, Assoc::Left),
+];
+
+pub const UNARY_OPERATORS: [(&str, u8); 2] = [
+    ("-", 3),
+    ("!", 8),
+];
+
+// Lambdas, `if`, `let`, `with` and `assert` extend as far to the right as
+// possible, so they always need parentheses as operands.
+pub const OPEN_ENDED: u8 = 100;
+
+pub fn binary_info(op: &str) -> Option<(u8, Assoc)> {
+    BINARY_OPERATORS.iter()
+        .find(|(symbol, _, _)| *symbol == op)
+        .map(|(_, precedence, assoc)| (*precedence, *assoc))
+}
+
+pub fn operator_token(node: &SyntaxNode) -> Option<String> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| !token.kind().is_trivia())
+        .map(|token| token.text().to_string())
+}
+
+pub fn precedence(node: &SyntaxNode) -> u8 {
+    match node.kind() {
+        SyntaxKind::BINARY_OP => operator_token(node)
+            .and_then(|op| binary_info(&op))
+            .map(|(precedence, _)| precedence)
+            .unwrap_or(OPEN_ENDED),
+        SyntaxKind::UNARY_OP => operator_token(node)
+            .and_then(|op| UNARY_OPERATORS.iter().find(|(symbol, _)| *symbol == op))
+            .map(|(_, precedence)| *precedence)
+            .unwrap_or(OPEN_ENDED),
+        SyntaxKind::HAS_ATTR => 4,
+        SyntaxKind::LAMBDA
+        | SyntaxKind::IF_THEN_ELSE
+        | SyntaxKind::LET_IN
+        | SyntaxKind::WITH
+        | SyntaxKind::ASSERT => OPEN_ENDED,
+        SyntaxKind::APPLY => 2,
+        _ => 0,
+    }
+}
+
+/// Whether an operand of the given precedence has to be parenthesized at
+/// position `is_rhs` of the binary operator `op`.
+pub fn operand_needs_parens(operand: u8, op: &str, is_rhs: bool) -> bool {
+    let Some((precedence, assoc)) = binary_info(op) else {
+        return true;
+    };
+    if operand != precedence {
+        return operand > precedence;
+    }
+    match assoc {
+        Assoc::Left => is_rhs,
+        Assoc::Right => !is_rhs,
+        Assoc::None => true,
+    }
+}
+
+fn wrap(text: String, parens: bool) -> String {
+    if parens { format!("({text})") } else { text }
+}
+
+/// The operands of a chain like `a // b // c` that uses the same operator in
+/// the direction it associates.
+pub fn flatten_chain(node: &SyntaxNode) -> Vec<SyntaxNode> {
+    let Some(op) = operator_token(node) else {
+        return vec![node.clone()];
+    };
+    let operands = node.children().collect::<Vec<_>>();
+    if operands.len() != 2 {
+        return operands;
+    }
+    let same_op = |n: &SyntaxNode| n.kind() == SyntaxKind::BINARY_OP && operator_token(n).as_deref() == Some(op.as_str());
+    match binary_info(&op).map(|(_, assoc)| assoc) {
+        Some(Assoc::Left) if same_op(&operands[0]) => {
+            let mut chain = flatten_chain(&operands[0]);
+            chain.push(operands[1].clone());
+            chain
+        },
+        Some(Assoc::Right) if same_op(&operands[1]) => {
+            let mut chain = vec![operands[0].clone()];
+            chain.extend(flatten_chain(&operands[1]));
+            chain
+        },
+        _ => operands,
+    }
+}
+
+/// Joins operand texts with `op`, parenthesizing operands where needed.
+/// `operands` pairs each text with the precedence of its expression.
+pub fn join_operands(operands: &[(String, u8)], op: &str) -> String {
+    let last = operands.len().saturating_sub(1);
+    let assoc = binary_info(op).map(|(_, assoc)| assoc).unwrap_or(Assoc::None);
+    operands.iter().enumerate()
+        .map(|(i, (text, precedence))| {
+            // In a chain every operand but the outermost ones is on both sides.
+            let parens = match assoc {
+                Assoc::Left => operand_needs_parens(*precedence, op, i > 0),
+                Assoc::Right => operand_needs_parens(*precedence, op, i == last && i > 0),
+                Assoc::None => operand_needs_parens(*precedence, op, i > 0),
+            };
+            wrap(text.clone(), parens)
+        })
+        .collect::<Vec<String>>()
+        .join(&format!(" {op} "))
+}
+
+/// Wraps the replacement for `node` in parentheses if its new precedence no
+/// longer fits the position of `node` in its parent.
+pub fn fit_into_parent(node: &SyntaxNode, text: String, precedence: u8) -> String {
+    let Some(parent) = node.parent() else {
+        return text;
+    };
+    let parens = match parent.kind() {
+        SyntaxKind::BINARY_OP => {
+            let is_rhs = parent.children().nth(1).as_ref() == Some(node);
+            operator_token(&parent)
+                .is_some_and(|op| operand_needs_parens(precedence, &op, is_rhs))
+        },
+        SyntaxKind::UNARY_OP => precedence > self::precedence(&parent),
+        SyntaxKind::APPLY | SyntaxKind::SELECT | SyntaxKind::HAS_ATTR => precedence > 0,
+        _ => false,
+    };
+    wrap(text, parens)
+}
@@ -0,0 +1,224 @@
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, HasBindings};
+
+use crate::ast::{attr_key, attrpath_keys};
+
+pub fn is_rec(set: &SyntaxNode) -> bool {
+    set.children_with_tokens()
+        .any(|element| element.as_token().is_some_and(|token| token.text() == "rec"))
+}
+
+fn binding_value(scope: &impl HasBindings, name: &str) -> Option<SyntaxNode> {
+    scope.bindings().find_map(|binding| match binding {
+        syntax::ast::Binding::AttrpathValue(attr) => {
+            let keys = attrpath_keys(&attr.attrpath()?);
+            (keys == [name]).then(|| attr.value().map(|v| v.syntax().clone())).flatten()
+        },
+        _ => None,
+    })
+}
+
+pub fn lambda_param_names(lambda: &SyntaxNode) -> Vec<String> {
+    let Some(param) = lambda.children().find(|c| c.kind() == SyntaxKind::PARAM) else {
+        return Vec::new();
+    };
+    param.children().flat_map(|child| match child.kind() {
+        SyntaxKind::NAME => vec![child.text().to_string()],
+        SyntaxKind::PAT => formals(&child).into_iter().map(|f| f.name).collect(),
+        _ => Vec::new(),
+    }).collect()
+}
+
+/// Finds the value a reference is bound to by `let` or `rec { }`, following the
+/// lexical scopes outwards. Lambda parameters and `with` scopes have no known
+/// value and end the search.
+pub fn resolve_local(reference: &SyntaxNode) -> Option<SyntaxNode> {
+    let name = syntax::ast::Ref::cast(reference.clone())?.token()?.text().to_string();
+    for scope in reference.ancestors().skip(1) {
+        match scope.kind() {
+            SyntaxKind::LET_IN => {
+                let let_in = syntax::ast::LetIn::cast(scope.clone())?;
+                if let Some(value) = binding_value(&let_in, &name) {
+                    return Some(value);
+                }
+            },
+            SyntaxKind::ATTR_SET if is_rec(&scope) => {
+                let set = syntax::ast::AttrSet::cast(scope.clone())?;
+                if let Some(value) = binding_value(&set, &name) {
+                    return Some(value);
+                }
+            },
+            SyntaxKind::LAMBDA => {
+                if lambda_param_names(&scope).contains(&name) {
+                    return None;
+                }
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+// Builtins that are in scope without `builtins.`. They are bound outside of
+// every `with`, so a `with` never provides them.
+const GLOBALS: [&str; 22] = [
+    "true", "false", "null", "builtins", "import", "toString", "map", "throw",
+    "abort", "derivation", "derivationStrict", "baseNameOf", "dirOf", "isNull",
+    "removeAttrs", "fetchTarball", "fetchGit", "fetchMercurial", "fetchTree",
+    "fromTOML", "scopedImport", "placeholder",
+];
+
+fn binding_names(scope: &impl HasBindings) -> Vec<String> {
+    scope.bindings().flat_map(|binding| match binding {
+        syntax::ast::Binding::AttrpathValue(attr) => attr.attrpath()
+            .and_then(|ap| attrpath_keys(&ap).into_iter().next())
+            .into_iter()
+            .collect::<Vec<_>>(),
+        syntax::ast::Binding::Inherit(inherit) => inherit.attrs()
+            .map(|attr| attr_key(&attr))
+            .collect(),
+    }).collect()
+}
+
+fn binds_name(scope: &SyntaxNode, name: &str) -> bool {
+    let name = name.to_string();
+    match scope.kind() {
+        SyntaxKind::LET_IN => syntax::ast::LetIn::cast(scope.clone())
+            .is_some_and(|let_in| binding_names(&let_in).contains(&name)),
+        SyntaxKind::ATTR_SET if is_rec(scope) => syntax::ast::AttrSet::cast(scope.clone())
+            .is_some_and(|set| binding_names(&set).contains(&name)),
+        SyntaxKind::LAMBDA => lambda_param_names(scope).contains(&name),
+        _ => false,
+    }
+}
+
+/// The `let`, `rec { }` or lambda that binds `name` as seen from `node`.
+pub fn binding_scope(node: &SyntaxNode, name: &str) -> Option<SyntaxNode> {
+    node.ancestors().skip(1).find(|scope| binds_name(scope, name))
+}
+
+/// Names bound by the rec set `set` that are referred to from inside it. These
+/// references break when `rec` is removed.
+pub fn rec_dependents(set: &SyntaxNode) -> Vec<String> {
+    let mut names = set.descendants()
+        .filter_map(syntax::ast::Ref::cast)
+        .filter_map(|reference| {
+            let name = reference.token()?.text().to_string();
+            (binding_scope(reference.syntax(), &name).as_ref() == Some(set)).then_some(name)
+        })
+        .collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Names that enclosing `rec { }` sets bring into scope at `node`.
+pub fn rec_bound_names(node: &SyntaxNode) -> Vec<String> {
+    node.ancestors().skip(1)
+        .filter(|scope| scope.kind() == SyntaxKind::ATTR_SET && is_rec(scope))
+        .filter_map(syntax::ast::AttrSet::cast)
+        .flat_map(|set| binding_names(&set))
+        .collect()
+}
+
+/// The `with` expression that `name`, used at `node`, is taken from. Lexical
+/// bindings take precedence over every `with`, and the innermost `with` wins.
+pub fn with_scope_for(node: &SyntaxNode, name: &str) -> Option<SyntaxNode> {
+    if GLOBALS.contains(&name) {
+        return None;
+    }
+    let mut found = None;
+    let mut child = node.clone();
+    for scope in node.ancestors().skip(1) {
+        if binds_name(&scope, name) {
+            return None;
+        }
+        let in_body = scope.children().nth(1).as_ref() == Some(&child);
+        if scope.kind() == SyntaxKind::WITH && in_body && found.is_none() {
+            found = Some(scope.clone());
+        }
+        child = scope;
+    }
+    found
+}
+
+pub fn with_scope(reference: &SyntaxNode) -> Option<SyntaxNode> {
+    let name = syntax::ast::Ref::cast(reference.clone())?.token()?.text().to_string();
+    with_scope_for(reference, &name)
+}
+
+/// The body of `with` with every reference it provides replaced by an explicit
+/// selection from its namespace. `inherit x;` can't be rewritten in place, so
+/// bodies that inherit a name from the `with` are rejected.
+pub fn explicit_selections(with: &SyntaxNode) -> Option<String> {
+    let namespace = with.children().next()?;
+    let body = with.children().nth(1)?;
+    let prefix = match namespace.kind() {
+        SyntaxKind::REF | SyntaxKind::SELECT => namespace.text().to_string(),
+        _ => format!("({})", namespace.text()),
+    };
+    let inherits_from_with = body.descendants()
+        .filter(|node| node.kind() == SyntaxKind::INHERIT)
+        .filter(|inherit| !inherit.children().any(|c| c.kind() == SyntaxKind::PAREN))
+        .filter_map(syntax::ast::Inherit::cast)
+        .any(|inherit| {
+            // The name is looked up outside of the `let` or `rec { }` that the
+            // inherit itself binds it in.
+            let Some(scope) = inherit.syntax().parent() else {
+                return false;
+            };
+            inherit.attrs().any(|attr| {
+                with_scope_for(&scope, &attr_key(&attr)).as_ref() == Some(with)
+            })
+        });
+    if inherits_from_with {
+        return None;
+    }
+    let start = body.text_range().start();
+    let mut text = body.text().to_string();
+    let mut references = body.descendants()
+        .filter(|node| node.kind() == SyntaxKind::REF)
+        .filter(|reference| with_scope(reference).as_ref() == Some(with))
+        .collect::<Vec<_>>();
+    // Replace back to front so earlier offsets stay valid.
+    references.reverse();
+    for reference in references {
+        let range = reference.text_range() - start;
+        text.replace_range(
+            usize::from(range.start())..usize::from(range.end()),
+            &format!("{prefix}.{}", reference.text()),
+        );
+    }
+    Some(text)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Formal {
+    pub name: String,
+    pub has_default: bool,
+}
+
+fn formals(pat: &SyntaxNode) -> Vec<Formal> {
+    pat.children()
+        .filter(|field| field.kind() == SyntaxKind::PAT_FIELD)
+        .filter_map(|field| {
+            let name = field.children().find(|n| n.kind() == SyntaxKind::NAME)?;
+            Some(Formal {
+                name: name.text().to_string(),
+                has_default: field.children().any(|n| syntax::ast::Expr::can_cast(n.kind())),
+            })
+        })
+        .collect()
+}
+
+/// The formal parameters of a lambda with a `{ ... }` pattern, and whether the
+/// pattern has an ellipsis.
+pub fn lambda_formals(lambda: &SyntaxNode) -> Option<(Vec<Formal>, bool)> {
+    let pat = lambda.children()
+        .find(|c| c.kind() == SyntaxKind::PARAM)?
+        .children()
+        .find(|c| c.kind() == SyntaxKind::PAT)?;
+    let ellipsis = pat.children_with_tokens()
+        .any(|element| element.as_token().is_some_and(|token| token.text() == "..."));
+    Some((formals(&pat), ellipsis))
+}
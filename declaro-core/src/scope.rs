@@ -0,0 +1,64 @@
+use ide::{AnalysisHost, FileId};
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::{AstNode, SourceFile};
+
+use crate::error::EditError;
+
+/// The analysis of the file `root` is the root of, or `None` while it holds no
+/// expression. It is built from the whole file, comments included, so node
+/// pointers and ranges of the open document can be passed to it unchanged.
+pub fn analysis_for(root: &SyntaxNode) -> Option<(AnalysisHost, FileId)> {
+    SourceFile::cast(root.clone())?.expr()?;
+    Some(AnalysisHost::new_single_file(&root.to_string()))
+}
+
+/// The names visible at `node` according to nil's scope analysis.
+pub fn get_bindings_in_scope(node: &SyntaxNode, analysis: &(AnalysisHost, FileId)) -> Result<Vec<String>, EditError> {
+    let snapshot = analysis.0.snapshot();
+    let scopes = snapshot.scopes(analysis.1).map_err(|e| EditError::Analysis(e.to_string()))?;
+    let source_map = snapshot.source_map(analysis.1).map_err(|e| EditError::Analysis(e.to_string()))?;
+    let expr_id = source_map
+        .expr_for_node(SyntaxNodePtr::new(node))
+        .ok_or_else(|| EditError::Analysis("the node is not an expression".to_string()))?;
+    let scope_id = scopes.scope_for_expr(expr_id)
+        .ok_or_else(|| EditError::Analysis("the expression has no scope".to_string()))?;
+    Ok(scopes
+        .ancestors(scope_id)
+        .filter_map(|scope| scope.as_definitions())
+        .flatten()
+        .map(|(name, _def)| name.to_string())
+        .collect::<Vec<String>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syntax::ast::Ref;
+
+    // The names in scope at the first reference to `name` in `src`.
+    fn scope_at_ref(src: &str, name: &str) -> Vec<String> {
+        let root = syntax::parse_file(src).syntax_node();
+        let analysis = analysis_for(&root).unwrap();
+        let reference = root.descendants()
+            .filter_map(Ref::cast)
+            .find(|r| r.syntax().text() == name)
+            .unwrap();
+        get_bindings_in_scope(reference.syntax(), &analysis).unwrap()
+    }
+
+    #[test]
+    fn finds_bindings_of_enclosing_lets() {
+        assert!(scope_at_ref("let x = 1; in { y = x; }", "x").contains(&"x".to_string()));
+    }
+
+    #[test]
+    fn finds_bindings_below_a_header_comment() {
+        let scope = scope_at_ref("# header\n# more\nlet x = 1; in { y = x; }", "x");
+        assert!(scope.contains(&"x".to_string()));
+    }
+
+    #[test]
+    fn has_no_analysis_without_an_expression() {
+        assert!(analysis_for(&syntax::parse_file("# only a comment\n").syntax_node()).is_none());
+    }
+}
@@ -0,0 +1,67 @@
+//! Starts declaro with an extra editor for option declarations like
+//! `lib.mkOption { type = lib.types.bool; default = false; }`.
+
+use dioxus::prelude::*;
+use syntax::{SyntaxKind, SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+
+use declaro::{Editor, EditorRegistry, ExpressionUI};
+
+// The attribute set passed to `mkOption` or `lib.mkOption`.
+fn mk_option_argument(node: &SyntaxNode) -> Option<SyntaxNode> {
+    if node.kind() != SyntaxKind::APPLY {
+        return None;
+    }
+    let mut children = node.children();
+    let (function, argument) = (children.next()?, children.next()?);
+    let name = function.text().to_string();
+    let is_mk_option = name == "mkOption" || name == "lib.mkOption";
+    (is_mk_option && syntax::ast::AttrSet::can_cast(argument.kind())).then_some(argument)
+}
+
+struct MkOptionEditor;
+
+impl Editor for MkOptionEditor {
+    fn matches(&self, node: &SyntaxNode) -> bool {
+        mk_option_argument(node).is_some()
+    }
+
+    // Wins over the built-in editor for function calls.
+    fn priority(&self) -> i32 {
+        1
+    }
+
+    fn composed(&self) -> bool {
+        true
+    }
+
+    fn render(&self, ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+        rsx! { MkOptionUI { ptr: ptr, nesting_level: nesting_level } }
+    }
+
+    fn templates(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("Option", "lib.mkOption { type = lib.types.str; default = \"\"; }")]
+    }
+}
+
+#[component]
+fn MkOptionUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let node = ptr.read().to_node(&ast.read());
+    let Some(argument) = mk_option_argument(&node) else {
+        return rsx! {};
+    };
+    rsx! {
+        div {
+            class: "conditional-node",
+            span { class: "conditional-label", "option" }
+            ExpressionUI { ptr: SyntaxNodePtr::new(&argument), nesting_level: nesting_level }
+        }
+    }
+}
+
+fn main() {
+    let mut registry = EditorRegistry::builtin();
+    registry.register(MkOptionEditor);
+    declaro::launch(registry);
+}
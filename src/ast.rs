@@ -1,106 +1,13 @@
-use syntax::{SyntaxNode};
-use dioxus::prelude::*;
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct AstPath {
-    pub indices: Vec<usize>,
-}
-
-pub fn path_from_root(node: &SyntaxNode) -> AstPath {
-    let mut indices = Vec::new();
-    let mut current = node.clone();
-
-    while let Some(parent) = current.parent() {
-        let index = parent
-            .children()
-            .position(|child| child == current)
-            .expect("node must be child of its parent");
-
-        indices.push(index);
-        current = parent;
-    }
-
-    indices.reverse();
-
-    AstPath { indices }
-}
-
-pub fn resolve_path(root: &SyntaxNode, path: &AstPath) -> Option<SyntaxNode> {
-    let mut current = root.clone();
-
-    for &index in &path.indices {
-        current = current.children().nth(index)?;
-    }
-
-    Some(current)
-}
-
-pub struct IndexedNode {
-    pub index: AstPath,
-    pub node: SyntaxNode,
-}
-
-pub fn collect_path(root: SyntaxNode, path: &AstPath) -> Vec<IndexedNode> {
-    let mut nodes = Vec::new();
-    let mut current = root;
-    let mut current_path = AstPath { indices: Vec::new() };
-    let index_node = IndexedNode {
-        index: AstPath { indices: vec![0] },
-        node: current.clone(),
-    };
-    nodes.push(index_node);
-    for &index in &path.indices {
-        if let Some(child) = current.children().nth(index) {
-            current_path.indices.push(index);
-            let index_node = IndexedNode {
-                index: current_path.clone(),
-                node: child.clone(),
-            };
-            nodes.push(index_node);
-            current = child;
-        } else {
-            break;
-        }
-    }
-    nodes
-}
-
-impl std::fmt::Display for AstPath {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, idx) in self.indices.iter().enumerate() {
-            if i > 0 {
-                write!(f, ".")?;
-            }
-            write!(f, "{idx}")?;
-        }
-        Ok(())
-    }
-}
+//! The editor's view of the syntax tree: the pure operations of
+//! `declaro_core` plus wrappers that apply an edit to the open document.
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseAstPathError;
-
-impl std::fmt::Display for ParseAstPathError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "failed to parse AstPath")
-    }
-}
-
-impl std::str::FromStr for AstPath {
-    type Err = ParseAstPathError;
+use syntax::SyntaxNode;
+use dioxus::prelude::*;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let indices = if s.is_empty() {
-            Ok(Vec::new())
-        } else {
-            s.split('.')
-                .map(|p| p.parse::<usize>().map_err(|_| ()))
-                .collect::<Result<Vec<_>, _>>().map_err(|_| {ParseAstPathError})
-        };
-
-        indices.map(|indices| AstPath { indices } )
-    }
-}
+pub use declaro_core::ast::*;
+pub use declaro_core::edit::*;
+pub use declaro_core::path::*;
+use declaro_core::EditError;
 
 #[macro_export]
 macro_rules! use_ast_node_strict {
@@ -120,6 +27,25 @@ macro_rules! use_ast_node_strict {
     }};
 }
 
+/// Why the last edit was not possible, shown in the menu until the next
+/// edit succeeds or it is dismissed.
+#[derive(Clone, Copy)]
+pub struct EditStatus(pub Signal<Option<String>>);
+
+/// Replaces the open document with the result of an edit, or reports why
+/// the edit was not possible.
+pub fn apply_edit(edit: Result<SyntaxNode, EditError>) {
+    let mut ast = use_context::<Signal<SyntaxNode>>();
+    let EditStatus(mut status) = use_context::<EditStatus>();
+    match edit {
+        Ok(new_root) => {
+            ast.set(new_root);
+            status.set(None);
+        },
+        Err(e) => status.set(Some(format!("Edit failed: {e}"))),
+    }
+}
+
 pub fn update_node_value<F>(
     node: SyntaxNode,
     new_value: &str,
@@ -128,39 +54,26 @@ pub fn update_node_value<F>(
 where
     F: Fn(&SyntaxNode) -> Option<SyntaxNode>,
 {
-    let mut ast = use_context::<Signal<SyntaxNode>>();
-    let new_syntax = syntax::parse_file(new_value).syntax_node();
-    if let Some(new_syntax) = extract_new_node(&new_syntax) {
-        let new_root = SyntaxNode::new_root(
-            replace_expr(&node, new_syntax)
-        );
-        ast.set(new_root);
-    }
+    apply_edit(with_node_value(&node, new_value, extract_new_node));
 }
 
-fn replace_expr(
-    old: &SyntaxNode,
-    new: SyntaxNode,
-) -> rowan::GreenNode {
-    let parent = old.parent().expect("expr must have parent");
-
-    let mut children: Vec<rowan::NodeOrToken<rowan::GreenNode,rowan::GreenToken>> =
-        parent.green().children().map(|c| {
-            c.to_owned()
-        }).collect();
-
-    let idx = parent
-        .children_with_tokens()
-        .position(|c| c.as_node().is_some() && c.as_node().unwrap() == old)
-        .unwrap();
-
-    children.iter().for_each(|c| println!("Child: {:?}", c.kind()));
-    children[idx] = rowan::NodeOrToken::Node(new.green().into_owned());
-    println!("Replaced child at index {}, with {:?}", idx, new);
+pub fn set_rec(set: SyntaxNode, rec: bool) {
+    apply_edit(with_rec(&set, rec));
+}
 
+pub fn remove_node(node: SyntaxNode) {
+    apply_edit(without_node(&node));
+}
 
-    let new_parent = rowan::GreenNode::new(rowan::SyntaxKind(parent.kind() as u16), children);
+pub fn remove_nodes(nodes: &[SyntaxNode]) {
+    let root = use_context::<Signal<SyntaxNode>>().read().clone();
+    apply_edit(without_nodes(&root, nodes));
+}
 
-    parent.replace_with(new_parent)
+pub fn insert_binding(set: SyntaxNode, binding: &str) {
+    apply_edit(with_binding(&set, binding));
 }
 
+pub fn replace_with_bindings(node: SyntaxNode, bindings: &str) {
+    apply_edit(with_replaced_bindings(&node, bindings));
+}
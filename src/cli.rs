@@ -0,0 +1,164 @@
+use std::fs;
+use declaro_core::document::parse_attrpath;
+use declaro_core::{Document, EditError};
+
+const COMMANDS: [&str; 4] = ["get", "set", "delete", "list"];
+
+const USAGE: &str = "usage:
+  declaro get <file> <path> [--nix]   print the value at path as JSON, or as Nix source
+  declaro set <file> <path> <value>   set path to the Nix expression value
+  declaro delete <file> <path>        remove everything defined below path
+  declaro list <file> [<path>]        print the attribute paths below path";
+
+#[derive(Debug)]
+pub enum CliError {
+    Usage,
+    Io(String),
+    Edit(EditError),
+}
+
+impl From<EditError> for CliError {
+    fn from(error: EditError) -> Self {
+        CliError::Edit(error)
+    }
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Edit(EditError::Missing(_)) => 1,
+            CliError::Edit(_) => 2,
+            CliError::Io(_) => 3,
+            CliError::Usage => 64,
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Usage => write!(f, "{USAGE}"),
+            CliError::Io(message) => write!(f, "{message}"),
+            CliError::Edit(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// Whether the arguments ask for a headless command instead of the GUI.
+pub fn is_command(args: &[String]) -> bool {
+    args.first().is_some_and(|command| COMMANDS.contains(&command.as_str()))
+}
+
+/// Runs a command and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    match execute(args) {
+        Ok(output) => {
+            if !output.is_empty() {
+                println!("{output}");
+            }
+            0
+        },
+        Err(e) => {
+            eprintln!("declaro: {e}");
+            e.exit_code()
+        },
+    }
+}
+
+fn execute(args: &[String]) -> Result<String, CliError> {
+    let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+    match args.as_slice() {
+        ["get", file, path] => {
+            let value = read_document(file)?.get_json(&parse_attrpath(path)?)?;
+            serde_json::to_string_pretty(&value).map_err(|e| CliError::Io(e.to_string()))
+        },
+        ["get", file, path, "--nix"] => Ok(read_document(file)?.get_source(&parse_attrpath(path)?)?),
+        ["set", file, path, value] => {
+            let document = read_document(file)?.set(&parse_attrpath(path)?, value)?;
+            write_document(file, &document).map(|_| String::new())
+        },
+        ["delete", file, path] => {
+            let document = read_document(file)?.delete(&parse_attrpath(path)?)?;
+            write_document(file, &document).map(|_| String::new())
+        },
+        ["list", file] => Ok(read_document(file)?.list(&[])?.join("\n")),
+        ["list", file, path] => Ok(read_document(file)?.list(&parse_attrpath(path)?)?.join("\n")),
+        _ => Err(CliError::Usage),
+    }
+}
+
+fn read_document(file: &str) -> Result<Document, CliError> {
+    let contents = fs::read_to_string(file).map_err(|e| CliError::Io(format!("{file}: {e}")))?;
+    Ok(Document::parse(&contents)?)
+}
+
+// The whole text is written back, so comments around the expression survive.
+fn write_document(file: &str, document: &Document) -> Result<(), CliError> {
+    fs::write(file, document.text()).map_err(|e| CliError::Io(format!("{file}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "# Machine configuration
+{
+  # Remote access
+  services.openssh.enable = false;
+}
+";
+
+    // A config file of its own for every test, since tests run in parallel.
+    fn config_file(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("declaro-{}-{name}.nix", std::process::id()));
+        fs::write(&path, CONFIG).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn recognises_commands() {
+        assert!(is_command(&args(&["get", "a.nix", "a"])));
+        assert!(!is_command(&args(&["a.nix"])));
+        assert!(!is_command(&[]));
+    }
+
+    #[test]
+    fn prints_values_as_json_or_nix() {
+        let file = config_file("get");
+        assert_eq!(execute(&args(&["get", &file, "services"])).unwrap(), "{\n  \"openssh\": {\n    \"enable\": false\n  }\n}");
+        assert_eq!(execute(&args(&["get", &file, "services.openssh.enable", "--nix"])).unwrap(), "false");
+        assert_eq!(execute(&args(&["list", &file])).unwrap(), "services.openssh.enable");
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn edits_keep_comments() {
+        let file = config_file("edit");
+        assert_eq!(run(&args(&["set", &file, "services.openssh.enable", "true"])), 0);
+        assert_eq!(fs::read_to_string(&file).unwrap(), CONFIG.replace("false", "true"));
+
+        assert_eq!(run(&args(&["delete", &file, "services"])), 0);
+        let contents = fs::read_to_string(&file).unwrap();
+        assert!(contents.starts_with("# Machine configuration\n{"));
+        assert!(contents.ends_with("}\n"));
+        assert!(!contents.contains("services"));
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn exit_codes_tell_failures_apart() {
+        let file = config_file("exit");
+        assert_eq!(run(&args(&["get", &file, "services.openssh"])), 0);
+        assert_eq!(run(&args(&["get", &file, "boot"])), 1);
+        assert_eq!(run(&args(&["set", &file, "services.openssh.enable.x", "1"])), 2);
+        assert_eq!(run(&args(&["set", &file, "a", "{"])), 2);
+        assert_eq!(run(&args(&["get", "/nonexistent/declaro.nix", "a"])), 3);
+        assert_eq!(run(&args(&["get", &file])), 64);
+        assert_eq!(fs::read_to_string(&file).unwrap(), CONFIG);
+        fs::remove_file(file).unwrap();
+    }
+}
@@ -3,8 +3,29 @@ pub mod string_input;
 pub mod ref_input;
 pub mod lambda;
 pub mod expression;
+pub mod number_input;
+pub mod bool_input;
+pub mod path_input;
+pub mod apply;
+pub mod operator;
+pub mod conditional;
+pub mod with;
+pub mod inherit;
+pub mod select;
+pub mod list;
+pub mod notes;
+pub mod registry;
+pub mod options;
+pub mod package_picker;
+pub mod flake_view;
+pub mod lock_graph;
+pub mod source_view;
+pub mod diff_view;
+pub mod git_panel;
+pub mod outline;
+pub mod search;
 use dioxus::prelude::*;
-use syntax::{match_ast, SyntaxNode, SyntaxNodePtr};
+use syntax::{SyntaxKind, SyntaxNode, SyntaxNodePtr};
 use syntax::ast::AstNode;
 use crate::router::Route;
 
@@ -15,11 +36,26 @@ use crate::ast::{collect_path, resolve_path, AstPath, update_node_value, path_fr
 #[component]
 pub fn NodeUI(path: ReadSignal<AstPath>) -> Element {
     let ast = use_context::<Signal<SyntaxNode>>();
+    // Edits in the source pane can remove the node this route points to,
+    // so the pointer falls back to the root until the route changes.
     let ptr = use_memo(move || {
-        let node = resolve_path(&ast.read(), &path.read()).unwrap();
+        let root = ast.read();
+        let node = resolve_path(&root, &path.read()).unwrap_or_else(|| root.clone());
         println!("NodeUI rendering node: {}", node.to_string());
         SyntaxNodePtr::new(&node)
     });
+    if resolve_path(&ast.read(), &path.read()).is_none() {
+        return rsx! {
+            div {
+                class: "missing-node",
+                "This node no longer exists. "
+                Link {
+                    to: Route::NodeUI { path: AstPath { indices: vec![0] } },
+                    "Back to root"
+                }
+            }
+        };
+    }
     let level: u16 = 0;
     rsx! {
         Nav { path: path() }
@@ -28,41 +64,176 @@ pub fn NodeUI(path: ReadSignal<AstPath>) -> Element {
 }
 
 
+fn lambda_label(lambda: &SyntaxNode) -> String {
+    let Some(param) = lambda.children().find(|c| c.kind() == SyntaxKind::PARAM) else {
+        return "λ".to_string();
+    };
+    let names = param.children().filter_map(|child| match child.kind() {
+        SyntaxKind::NAME => Some(child.text().to_string()),
+        SyntaxKind::PAT => {
+            let fields = child.children()
+                .filter(|field| field.kind() == SyntaxKind::PAT_FIELD)
+                .filter_map(|field| field.children().find(|n| n.kind() == SyntaxKind::NAME))
+                .map(|name| name.text().to_string())
+                .collect::<Vec<String>>();
+            Some(format!("{{ {} }}", fields.join(", ")))
+        },
+        _ => None,
+    }).collect::<Vec<String>>();
+    format!("λ {}", names.join(" @ "))
+}
+
+fn apply_head(apply: &SyntaxNode) -> (String, usize) {
+    let mut arity = 0;
+    let mut current = apply.clone();
+    while current.kind() == SyntaxKind::APPLY {
+        arity += 1;
+        match current.children().next() {
+            Some(function) => current = function,
+            None => break,
+        }
+    }
+    (current.text().to_string(), arity)
+}
+
+// Label of the breadcrumb that enters `child` from `parent`, if `child` is an
+// expression that can be navigated to.
+fn step_label(parent: &SyntaxNode, child: &SyntaxNode) -> Option<String> {
+    if !syntax::ast::Expr::can_cast(child.kind()) {
+        return None;
+    }
+    let position = parent.children()
+        .filter(|c| syntax::ast::Expr::can_cast(c.kind()))
+        .position(|c| &c == child)?;
+    let label = match parent.kind() {
+        SyntaxKind::SOURCE_FILE => "root".to_string(),
+        SyntaxKind::ATTR_PATH_VALUE => syntax::ast::AttrpathValue::cast(parent.clone())
+            .and_then(|attr| attr.attrpath())
+            .map(|ap| ap.syntax().text().to_string())
+            .unwrap_or("unknown".to_string()),
+        SyntaxKind::PAT_FIELD => {
+            let name = parent.children()
+                .find(|n| n.kind() == SyntaxKind::NAME)
+                .map(|n| n.text().to_string())
+                .unwrap_or_default();
+            format!("{name} ? default")
+        },
+        SyntaxKind::LAMBDA => lambda_label(parent),
+        SyntaxKind::LET_IN => "let … in".to_string(),
+        SyntaxKind::WITH => {
+            let namespace = parent.children().next()
+                .map(|n| n.text().to_string())
+                .unwrap_or_default();
+            if position == 0 { "with namespace".to_string() } else { format!("with {namespace}") }
+        },
+        SyntaxKind::LIST => format!("[{position}]"),
+        SyntaxKind::APPLY => {
+            let (head, arity) = apply_head(parent);
+            if position == 0 { "function".to_string() } else { format!("{head} ‹arg {arity}›") }
+        },
+        SyntaxKind::IF_THEN_ELSE => ["if", "then", "else"].get(position)?.to_string(),
+        SyntaxKind::ASSERT => ["assert", "assert … ;"].get(position)?.to_string(),
+        SyntaxKind::SELECT => ["base", "or default"].get(position)?.to_string(),
+        SyntaxKind::BINARY_OP => ["left operand", "right operand"].get(position)?.to_string(),
+        SyntaxKind::UNARY_OP => "operand".to_string(),
+        SyntaxKind::HAS_ATTR => "set".to_string(),
+        SyntaxKind::DYNAMIC => "${ }".to_string(),
+        // Parentheses don't introduce a level worth showing.
+        SyntaxKind::PAREN => return None,
+        _ => return None,
+    };
+    Some(label)
+}
+
+// Every navigable child of a node, looking through bindings to their values.
+fn child_steps(container: &SyntaxNode) -> Vec<(String, SyntaxNode)> {
+    container.children().flat_map(|child| {
+        if child.kind() == SyntaxKind::ATTR_PATH_VALUE {
+            child.children()
+                .filter_map(|value| step_label(&child, &value).map(|label| (label, value)))
+                .collect::<Vec<_>>()
+        } else {
+            step_label(container, &child)
+                .map(|label| vec![(label, child)])
+                .unwrap_or_default()
+        }
+    }).collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crumb {
+    pub label: String,
+    pub target: AstPath,
+    pub siblings: Vec<(String, AstPath)>,
+}
+
+pub fn breadcrumbs(root: SyntaxNode, path: &AstPath) -> Vec<Crumb> {
+    let nodes = collect_path(root, path);
+    nodes.windows(2).filter_map(|pair| {
+        let (parent, child) = (&pair[0].node, &pair[1].node);
+        let label = step_label(parent, child)?;
+        let container = if parent.kind() == SyntaxKind::ATTR_PATH_VALUE {
+            parent.parent().unwrap_or(parent.clone())
+        } else {
+            parent.clone()
+        };
+        let siblings = child_steps(&container).into_iter()
+            .filter(|(_, node)| node != child)
+            .map(|(label, node)| (label, path_from_root(&node)))
+            .collect();
+        Some(Crumb { label, target: pair[1].index.clone(), siblings })
+    }).collect()
+}
+
 #[component]
 pub fn Nav(path: AstPath) -> Element {
     let ast = use_context::<Signal<SyntaxNode>>();
-    let nodes = collect_path(ast.read().clone(), &path);
-    println!("Nav nodes: {:?}", nodes.iter().map(|n| n.node.kind().to_string()).collect::<Vec<String>>());
-    let elements = nodes.iter().filter_map(|index_node|{
-        let node = &index_node.node;
-        match_ast! {
-            match node {
-                syntax::ast::AttrpathValue(attr) => {
-                    let mut index = index_node.index.clone();
-                    index.indices.push(1); // The second child of an AttrpathValue is the value,
-                                        // which is what we want to link to.
-                    let label = attr.attrpath()
-                        .map(|ap| ap.syntax().text().to_string())
-                        .unwrap_or("unknown".to_string());
-                    Some((label,index))
-                },
-                syntax::ast::SourceFile(_sf) => {
-                    let index = index_node.index.clone();
-                    let label = "root".to_string();
-                    Some((label,index))
-                },
-                _ => None,
-            }
+    let crumbs = breadcrumbs(ast.read().clone(), &path);
+    let elements = crumbs.into_iter().map(|crumb| {
+        rsx! {
+            CrumbUI { crumb: crumb }
         }
-    }).map(|link| {
+    });
+    rsx! {
+        nav { {elements} }
+    }
+}
+
+#[component]
+fn CrumbUI(crumb: Crumb) -> Element {
+    let mut open = use_signal(|| false);
+    let has_siblings = !crumb.siblings.is_empty();
+    let siblings = crumb.siblings.into_iter().map(|(label, target)| {
         rsx! {
-            Link {
-                to: Route::NodeUI{ path: link.1},
-                {link.0}
+            li {
+                Link {
+                    to: Route::NodeUI{ path: target },
+                    onclick: move |_| open.set(false),
+                    {label}
+                }
             }
         }
     });
     rsx! {
-        nav { {elements} }
+        span {
+            class: "crumb",
+            Link {
+                to: Route::NodeUI{ path: crumb.target },
+                {crumb.label}
+            }
+            if has_siblings {
+                button {
+                    class: "crumb-siblings-toggle",
+                    onclick: move |_| open.set(!open()),
+                    "▾"
+                }
+            }
+            if open() {
+                ul {
+                    class: "crumb-siblings",
+                    { siblings }
+                }
+            }
+        }
     }
 }
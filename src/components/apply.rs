@@ -0,0 +1,158 @@
+use syntax::{SyntaxKind, SyntaxNode, SyntaxNodePtr};
+use syntax::ast::{AstNode, HasBindings};
+use dioxus::prelude::*;
+use closure::closure;
+
+use crate::components::ExpressionUI;
+use crate::{use_ast_node_strict};
+use crate::ast::{attr_key, attrpath_keys, update_node_value};
+use crate::resolve::{lambda_formals, resolve_local};
+
+/// Splits the curried application `f a b` into `f` and `[a, b]`.
+pub fn flatten_apply(apply: &SyntaxNode) -> (SyntaxNode, Vec<SyntaxNode>) {
+    let mut args = Vec::new();
+    let mut current = apply.clone();
+    while current.kind() == SyntaxKind::APPLY {
+        let mut children = current.children();
+        let (Some(function), Some(argument)) = (children.next(), children.next()) else {
+            break;
+        };
+        args.push(argument);
+        current = function;
+    }
+    args.reverse();
+    (current, args)
+}
+
+fn supplied_names(argument: &SyntaxNode) -> Option<Vec<String>> {
+    let set = syntax::ast::AttrSet::cast(argument.clone())?;
+    Some(set.bindings().flat_map(|binding| match binding {
+        syntax::ast::Binding::AttrpathValue(attr) => attr.attrpath()
+            .and_then(|ap| attrpath_keys(&ap).into_iter().next())
+            .into_iter()
+            .collect::<Vec<_>>(),
+        syntax::ast::Binding::Inherit(inherit) => inherit.attrs()
+            .map(|attr| attr_key(&attr))
+            .collect(),
+    }).collect())
+}
+
+fn update_apply(apply: &SyntaxNode, function: &SyntaxNode, args: &[String]) {
+    let text = std::iter::once(function.text().to_string())
+        .chain(args.iter().cloned())
+        .collect::<Vec<String>>()
+        .join(" ");
+    update_node_value(
+        apply.clone(),
+        &text,
+        |syntax| {
+            <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                .and_then(|sf| sf.expr())
+                .map(|expr| expr.syntax().clone())
+        }
+    );
+}
+
+#[component]
+pub fn ApplyUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let apply = use_ast_node_strict!(ptr => syntax::ast::Apply);
+    let apply_node = apply.read().syntax().clone();
+    let (function, args) = flatten_apply(&apply_node);
+    let arg_texts = args.iter().map(|arg| arg.text().to_string()).collect::<Vec<String>>();
+
+    let formals = if function.kind() == SyntaxKind::REF {
+        resolve_local(&function)
+            .filter(|value| value.kind() == SyntaxKind::LAMBDA)
+            .and_then(|lambda| lambda_formals(&lambda))
+    } else {
+        None
+    };
+    let supplied = args.first().and_then(supplied_names).unwrap_or_default();
+    let formal_elements = formals.map(|(formals, ellipsis)| {
+        let names = formals.iter().map(|formal| formal.name.clone()).collect::<Vec<String>>();
+        let unexpected = if ellipsis {
+            Vec::new()
+        } else {
+            supplied.iter()
+                .filter(|name| !names.contains(name))
+                .cloned()
+                .collect::<Vec<String>>()
+        };
+        let chips = formals.into_iter().map(|formal| {
+            let class = if supplied.contains(&formal.name) {
+                "formal supplied"
+            } else if formal.has_default {
+                "formal optional"
+            } else {
+                "formal missing"
+            };
+            let name = formal.name;
+            rsx! { li { class: class, "{name}" } }
+        });
+        let unexpected = unexpected.into_iter().map(|name| rsx! {
+            li { class: "formal unexpected", title: "Not a parameter of the function", "{name}" }
+        });
+        rsx! {
+            div {
+                class: "apply-formals",
+                h4 { "Parameters:" }
+                ul {
+                    { chips }
+                    { unexpected }
+                }
+            }
+        }
+    });
+
+    let function_ptr = SyntaxNodePtr::new(&function);
+    let argument_elements = args.iter().enumerate().map(|(i, arg)| {
+        let arg_ptr = SyntaxNodePtr::new(arg);
+        let remove = closure!(clone apply_node, clone function, clone arg_texts, |_| {
+            let mut remaining = arg_texts.clone();
+            remaining.remove(i);
+            update_apply(&apply_node, &function, &remaining);
+        });
+        rsx! {
+            div {
+                class: "apply-argument",
+                label { class: "attribute-label", "argument {i + 1}" }
+                ExpressionUI { ptr: arg_ptr, nesting_level: nesting_level }
+                button {
+                    class: "apply-remove",
+                    title: "Remove argument",
+                    onclick: remove,
+                    "✕"
+                }
+            }
+        }
+    });
+    let add_node = apply_node.clone();
+    let add_function = function.clone();
+    let add_args = arg_texts.clone();
+
+    rsx! {
+        div {
+            class: "apply-node",
+            h3 { "Function call" }
+            div {
+                class: "apply-function",
+                label { class: "attribute-label", "function" }
+                ExpressionUI { ptr: function_ptr, nesting_level: nesting_level }
+            }
+            { formal_elements }
+            div {
+                class: "apply-arguments",
+                { argument_elements }
+            }
+            button {
+                class: "apply-add",
+                onclick: move |_| {
+                    let mut args = add_args.clone();
+                    args.push("{ }".to_string());
+                    update_apply(&add_node, &add_function, &args);
+                },
+                "Add argument"
+            }
+        }
+    }
+}
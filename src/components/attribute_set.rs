@@ -1,41 +1,387 @@
 use syntax::ast::{HasBindings};
-use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::{SyntaxKind, SyntaxNode, SyntaxNodePtr};
 use syntax::ast::AstNode;
 use dioxus::prelude::*;
 
 use crate::components::ExpressionUI;
+use crate::components::inherit::{can_inherit, to_inherit, InheritUI};
+use crate::components::notes::{MoveButtons, NotesUI};
+use crate::components::options::{enum_values, AddOption, EnumInput, OptionInfo};
 use crate::{use_ast_node_strict};
+use crate::ast::{attr_text, attrpath_keys, set_rec, update_node_value};
+use crate::attrpath::{
+    can_collapse, can_expand, collapse, display_attrpath, expand, has_nesting, logical_tree,
+    parse_attrpath_input, LogicalNode,
+};
+use crate::options::{option_prefix, LoadedOptions, OptionsRoot};
+use crate::resolve::{is_rec, rec_dependents};
+use crate::diff::{find_diff, removed_below, set_key, BindingDiff, DiffStatus};
+use crate::git::{can_revert, revert_binding, HeadDiff};
 
 #[component]
 pub fn AttributeSetUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
     let set = use_ast_node_strict!(ptr => syntax::ast::AttrSet);
+    let mut ast = use_context::<Signal<SyntaxNode>>();
+    let head_diff = use_context::<HeadDiff>().0;
+    let mut merged = use_signal(|| false);
+    let nested = has_nesting(&set.read());
+    let set_node = set.read().syntax().clone();
+    let prefix = set_key(&set_node);
+    let options = use_context::<LoadedOptions>().0;
+    let mut options_root = use_context::<OptionsRoot>().0;
+    let options_loaded = options.read().is_some();
+    let options_prefix = prefix.as_ref()
+        .filter(|_| options_loaded)
+        .and_then(|prefix| option_prefix(&options_root.read(), prefix));
     let elements = set.read().bindings()
-        .filter_map(|binding| match binding {
-            syntax::ast::Binding::AttrpathValue(attr) => Some(attr),
-            _ => None,
-                    })
-        .map(|attr| {
-            let label = attr.attrpath()
-                .map(|ap| ap.syntax().text().to_string())
-                .unwrap_or("unknown".to_string());
+        .map(|binding| {
+            let attr = match binding {
+                syntax::ast::Binding::AttrpathValue(attr) => attr,
+                syntax::ast::Binding::Inherit(inherit) => {
+                    let inherit_ptr = SyntaxNodePtr::new(inherit.syntax());
+                    return rsx! { InheritUI { ptr: inherit_ptr, nesting_level: nesting_level } };
+                },
+            };
+            let attrpath_ptr = attr.attrpath().map(|ap| SyntaxNodePtr::new(ap.syntax()));
+            let binding_ptr = SyntaxNodePtr::new(attr.syntax());
+            let binding_node = attr.syntax().clone();
             let value = attr.value().unwrap();
             let node = value.syntax();
             let ptr = SyntaxNodePtr::new(node);
+            let diff = prefix.as_ref().zip(attr.attrpath()).and_then(|(prefix, ap)| {
+                let mut key = prefix.clone();
+                key.extend(attrpath_keys(&ap));
+                find_diff(&head_diff.read(), &key).cloned()
+            });
+            let option = options_prefix.as_ref().zip(attr.attrpath()).and_then(|(prefix, ap)| {
+                let mut path = prefix.clone();
+                path.extend(attrpath_keys(&ap));
+                options.read().as_ref()?.find(&path).cloned()
+            });
+            let unknown = options_prefix.as_ref().zip(attr.attrpath())
+                .filter(|_| option.is_none())
+                .and_then(|(prefix, ap)| {
+                    let mut path = prefix.clone();
+                    path.extend(attrpath_keys(&ap));
+                    let (segment, suggestions) = options.read().as_ref()?.unknown_segment(&path)?;
+                    // Index of the segment within this binding's attrpath.
+                    let index = segment.checked_sub(prefix.len())?;
+                    Some(UnknownOption {
+                        name: path[segment].clone(),
+                        index,
+                        suggestions,
+                        attrpath: SyntaxNodePtr::new(ap.syntax()),
+                    })
+                });
+            let values = option.as_ref()
+                .and_then(|option| enum_values(&option.option_type))
+                .filter(|_| matches!(value, syntax::ast::Expr::String(_)));
+            let expandable = can_expand(&attr);
+            let collapsible = can_collapse(&attr);
+            let inheritable = can_inherit(&attr);
+            let expand_attr = attr.clone();
+            let inherit_attr = attr.clone();
         rsx! {
+            NotesUI { ptr: binding_ptr }
             div {
                 class: "attribute-item",
+                GitBadge { diff: diff, set: set_node.clone() }
+                MoveButtons { node: binding_node }
+                if let Some(attrpath_ptr) = attrpath_ptr {
+                    AttrpathInput { ptr: attrpath_ptr }
+                } else {
+                    label {
+                        class: "attribute-label",
+                        "unknown"
+                    }
+                }
+                if expandable {
+                    button {
+                        class: "attrpath-action",
+                        title: "Expand into nested sets",
+                        onclick: move |_| {
+                            if let Ok(new_root) = expand(&expand_attr) {
+                                ast.set(new_root);
+                            }
+                        },
+                        "⤢"
+                    }
+                }
+                if collapsible {
+                    button {
+                        class: "attrpath-action",
+                        title: "Collapse into a dotted path",
+                        onclick: move |_| {
+                            if let Ok(new_root) = collapse(&attr) {
+                                ast.set(new_root);
+                            }
+                        },
+                        "⤡"
+                    }
+                }
+                if inheritable {
+                    button {
+                        class: "attrpath-action",
+                        title: "Convert to inherit",
+                        onclick: move |_| to_inherit(&inherit_attr),
+                        "⇡"
+                    }
+                }
+                if let Some(values) = values {
+                    EnumInput { ptr: ptr, values: values }
+                } else {
+                    ExpressionUI { ptr: ptr, nesting_level: nesting_level }
+                }
+                if let Some(option) = option {
+                    OptionInfo { option: option }
+                }
+                if let Some(unknown) = unknown {
+                    UnknownOptionUI { unknown: unknown }
+                }
+            }
+        }
+    });
+    let root_key = prefix.clone().unwrap_or_default();
+    let options_footer = rsx! {
+        if let Some(options_prefix) = options_prefix.clone() {
+            AddOption { set: set_node.clone(), prefix: options_prefix }
+        }
+        if options_loaded && prefix.is_some() {
+            button {
+                class: "options-root",
+                title: "Option paths are relative to this set",
+                onclick: move |_| {
+                    options_root.set(root_key.clone());
+                },
+                "Use as options root"
+            }
+        }
+    };
+    let removed = prefix.as_ref()
+        .map(|prefix| removed_below(&head_diff.read(), prefix).into_iter().cloned().collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|diff| {
+            let label = diff.key[prefix.as_ref().map_or(0, Vec::len)..].join(".");
+            let old = diff.old.as_ref().map(|node| node.text().to_string()).unwrap_or_default();
+        rsx! {
+            div {
+                class: "attribute-item git-removed",
+                GitBadge { diff: Some(diff), set: set_node.clone() }
                 label {
                     class: "attribute-label",
                     "{label}"
                 }
-                ExpressionUI { ptr: ptr, nesting_level: nesting_level }
+                pre { class: "diff-text diff-old", "{old}" }
             }
         }
     });
+    if merged() {
+        let nodes = logical_tree(&set.read());
+        return rsx! {
+            div {
+                class: "attribute-set",
+                RecToggle { set: set_node.clone() }
+                MergeToggle { merged: merged }
+                LogicalSetUI { nodes: nodes, nesting_level: nesting_level }
+            }
+        };
+    }
     rsx! {
         div {
             class: "attribute-set",
+            RecToggle { set: set_node.clone() }
+            if nested {
+                MergeToggle { merged: merged }
+            }
             { elements }
+            { removed }
+            { options_footer }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct UnknownOption {
+    name: String,
+    index: usize,
+    suggestions: Vec<String>,
+    attrpath: SyntaxNodePtr,
+}
+
+#[component]
+fn UnknownOptionUI(unknown: UnknownOption) -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let suggestions = unknown.suggestions.iter().map(|suggestion| {
+        let suggestion = suggestion.clone();
+        let attrpath = unknown.attrpath.clone();
+        let index = unknown.index;
+        rsx! {
+            button {
+                class: "option-suggestion",
+                onclick: move |_| {
+                    let node = attrpath.to_node(&ast.read());
+                    let Some(ap) = syntax::ast::Attrpath::cast(node.clone()) else {
+                        return;
+                    };
+                    let mut segments = ap.attrs()
+                        .map(|attr| attr.syntax().text().to_string())
+                        .collect::<Vec<String>>();
+                    if index < segments.len() {
+                        segments[index] = attr_text(&suggestion);
+                    }
+                    update_node_value(
+                        node,
+                        &format!("{{ {} = null; }}", segments.join(".")),
+                        |syntax| syntax.descendants().find(|n| n.kind() == SyntaxKind::ATTR_PATH)
+                    );
+                },
+                "{suggestion}"
+            }
+        }
+    });
+    rsx! {
+        span {
+            class: "option-unknown",
+            "Unknown option {unknown.name}"
+            if !unknown.suggestions.is_empty() {
+                ", did you mean "
+                { suggestions }
+            }
+        }
+    }
+}
+
+#[component]
+fn AttrpathInput(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
+    let attrpath = use_ast_node_strict!(ptr => syntax::ast::Attrpath);
+    let value = display_attrpath(&attrpath.read());
+    rsx! {
+        input {
+            class: "attribute-label attrpath-input simple-input",
+            value: value,
+            onchange: move |e| {
+                let text = parse_attrpath_input(&e.value());
+                update_node_value(
+                    attrpath.read().syntax().clone(),
+                    &format!("{{ {text} = null; }}"),
+                    |syntax| syntax.descendants().find(|n| n.kind() == SyntaxKind::ATTR_PATH)
+                );
+            }
+        }
+    }
+}
+
+#[component]
+fn RecToggle(set: SyntaxNode) -> Element {
+    let mut confirming = use_signal(|| false);
+    let rec = is_rec(&set);
+    let dependents = if rec { rec_dependents(&set) } else { Vec::new() };
+    let warning = format!("Removing rec breaks references to {}", dependents.join(", "));
+    let toggle = move |_| {
+        if rec && !dependents.is_empty() && !confirming() {
+            confirming.set(true);
+            return;
+        }
+        confirming.set(false);
+        set_rec(set.clone(), !rec);
+    };
+    rsx! {
+        div {
+            class: "rec-toggle",
+            button {
+                class: if rec { "rec-button active" } else { "rec-button" },
+                title: if rec { "Bindings can refer to each other" } else { "Let bindings refer to each other" },
+                onclick: toggle,
+                if confirming() { "Remove rec anyway" } else { "rec" }
+            }
+            if confirming() {
+                span {
+                    class: "rec-warning",
+                    "{warning}"
+                }
+                button {
+                    class: "rec-button",
+                    onclick: move |_| confirming.set(false),
+                    "Keep rec"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn MergeToggle(mut merged: Signal<bool>) -> Element {
+    rsx! {
+        button {
+            class: "merge-toggle",
+            title: "Show dotted paths and nested sets as one tree",
+            onclick: move |_| merged.set(!merged()),
+            if merged() { "Show as written" } else { "Merge paths" }
+        }
+    }
+}
+
+#[component]
+fn LogicalSetUI(nodes: Vec<LogicalNode>, nesting_level: u16) -> Element {
+    let elements = nodes.into_iter().map(|node| {
+        let label = node.name.clone();
+        let body = match node.value {
+            Some(value) => {
+                let ptr = SyntaxNodePtr::new(&value);
+                rsx! { ExpressionUI { ptr: ptr, nesting_level: nesting_level } }
+            },
+            None => rsx! {
+                div {
+                    class: "attribute-set",
+                    LogicalSetUI { nodes: node.children, nesting_level: nesting_level }
+                }
+            },
+        };
+        rsx! {
+            div {
+                class: "attribute-item",
+                label {
+                    class: "attribute-label",
+                    "{label}"
+                }
+                { body }
+            }
+        }
+    });
+    rsx! {
+        { elements }
+    }
+}
+
+#[component]
+fn GitBadge(diff: Option<BindingDiff>, set: SyntaxNode) -> Element {
+    let Some(diff) = diff.filter(|d| d.status != DiffStatus::Unchanged) else {
+        return rsx! {};
+    };
+    let (symbol, title) = match diff.status {
+        DiffStatus::Added => ("+", "Added since HEAD"),
+        DiffStatus::Removed => ("−", "Removed since HEAD"),
+        _ => ("~", "Changed since HEAD"),
+    };
+    let class = diff.status.class();
+    let revertible = can_revert(&diff);
+    rsx! {
+        span {
+            class: "git-gutter",
+            span {
+                class: "git-badge {class}",
+                title: title,
+                "{symbol}"
+            }
+            if revertible {
+                button {
+                    class: "git-revert",
+                    title: "Revert to HEAD",
+                    onclick: move |_| revert_binding(&diff, set.clone()),
+                    "↺"
+                }
+            }
         }
     }
 }
@@ -0,0 +1,41 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use crate::{use_ast_node_strict};
+use crate::ast::{update_node_value};
+
+#[component]
+pub fn BoolInput(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
+    let node = use_ast_node_strict!(ptr => syntax::ast::Ref);
+    let checked = node.read().token().is_some_and(|t| t.text() == "true");
+    rsx! {
+        label {
+            class: "bool-input",
+            input {
+                r#type: "checkbox",
+                checked: checked,
+                onchange: move |_| {
+                    update_node_value(
+                        node.read().syntax().clone(),
+                        if checked { "false" } else { "true" },
+                        |syntax| {
+                            <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                                .and_then(|sf| sf.expr())
+                                .map(|expr| expr.syntax().clone())
+                        }
+                    );
+                }
+            }
+            span { class: "bool-slider" }
+            span { class: "bool-label", if checked { "true" } else { "false" } }
+        }
+    }
+}
+
+#[component]
+pub fn NullMarker() -> Element {
+    rsx! {
+        span { class: "null-marker", "null" }
+    }
+}
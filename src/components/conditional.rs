@@ -0,0 +1,69 @@
+use syntax::SyntaxNodePtr;
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use crate::components::ExpressionUI;
+use crate::use_ast_node_strict;
+
+#[component]
+pub fn IfElseUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let if_else = use_ast_node_strict!(ptr => syntax::ast::IfThenElse);
+    let branches = [
+        ("if", if_else.read().condition()),
+        ("then", if_else.read().then_body()),
+        ("else", if_else.read().else_body()),
+    ];
+
+    let elements = branches.into_iter().map(|(label, expr)| {
+        let branch = expr.map(|e| SyntaxNodePtr::new(e.syntax()));
+        rsx! {
+            div {
+                class: "conditional-branch",
+                span { class: "conditional-label", "{label}" }
+                if let Some(branch_ptr) = branch {
+                    ExpressionUI { ptr: branch_ptr, nesting_level: nesting_level }
+                } else {
+                    span { class: "conditional-missing", "missing" }
+                }
+            }
+        }
+    });
+
+    rsx! {
+        div {
+            class: "conditional-node",
+            { elements }
+        }
+    }
+}
+
+#[component]
+pub fn AssertUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let assert = use_ast_node_strict!(ptr => syntax::ast::Assert);
+    let parts = [
+        ("assert", assert.read().condition()),
+        (";", assert.read().body()),
+    ];
+
+    let elements = parts.into_iter().map(|(label, expr)| {
+        let part = expr.map(|e| SyntaxNodePtr::new(e.syntax()));
+        rsx! {
+            div {
+                class: "conditional-branch",
+                span { class: "conditional-label", "{label}" }
+                if let Some(part_ptr) = part {
+                    ExpressionUI { ptr: part_ptr, nesting_level: nesting_level }
+                } else {
+                    span { class: "conditional-missing", "missing" }
+                }
+            }
+        }
+    });
+
+    rsx! {
+        div {
+            class: "conditional-node assert",
+            { elements }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use crate::ast::AstPath;
+use crate::components::ExpressionUI;
+use crate::diff::{diff_roots, BindingDiff, DiffBase, DiffStatus};
+use crate::router::Route;
+
+#[component]
+pub fn DiffView() -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let mut base = use_context::<Signal<Option<DiffBase>>>();
+    let current_change = use_signal(|| 0usize);
+    let diffs = use_memo(move || {
+        base.read().as_ref()
+            .map(|b| diff_roots(&b.root, &ast.read()))
+            .unwrap_or_default()
+    });
+
+    let Some(label) = base.read().as_ref().map(|b| b.label.clone()) else {
+        return rsx! {
+            div {
+                class: "diff-view",
+                "Nothing to compare. Use \"Compare\" to pick a file."
+            }
+        };
+    };
+
+    let statuses = diffs.read().iter()
+        .flat_map(|diff| diff.changes())
+        .map(|diff| diff.status)
+        .collect::<Vec<DiffStatus>>();
+    let count = |status: DiffStatus| statuses.iter().filter(|s| **s == status).count();
+    let summary = format!(
+        "+{} -{} ~{}",
+        count(DiffStatus::Added),
+        count(DiffStatus::Removed),
+        count(DiffStatus::Changed),
+    );
+    let total = statuses.len();
+
+    let entries = diffs.read().clone().into_iter().map(|diff| rsx! {
+        DiffEntryUI { diff: diff }
+    });
+
+    rsx! {
+        div {
+            class: "diff-view",
+            div {
+                class: "diff-header",
+                span { "Comparing with {label}" }
+                span { class: "diff-summary", "{summary}" }
+                button { onclick: move |_| jump_to_change(diffs, current_change, -1), "Previous change" }
+                button { onclick: move |_| jump_to_change(diffs, current_change, 1), "Next change" }
+                Link {
+                    to: Route::NodeUI { path: AstPath { indices: vec![0] } },
+                    onclick: move |_| base.set(None),
+                    "Close"
+                }
+            }
+            if total == 0 {
+                div { class: "diff-empty", "No differences" }
+            }
+            div {
+                class: "attribute-set",
+                { entries }
+            }
+        }
+    }
+}
+
+fn jump_to_change(diffs: Memo<Vec<BindingDiff>>, mut current_change: Signal<usize>, offset: isize) {
+    let ids = diffs.read().iter()
+        .flat_map(|diff| diff.changes())
+        .map(|diff| diff.dom_id())
+        .collect::<Vec<String>>();
+    if ids.is_empty() {
+        return;
+    }
+    let next = (current_change() as isize + offset).rem_euclid(ids.len() as isize) as usize;
+    current_change.set(next);
+    // Keys can contain any character, so the id is passed as a JSON string.
+    let id = serde_json::to_string(&ids[next]).unwrap_or_default();
+    document::eval(&format!(
+        "document.getElementById({id})?.scrollIntoView({{ block: 'center', behavior: 'smooth' }});"
+    ));
+}
+
+#[component]
+fn DiffEntryUI(diff: BindingDiff) -> Element {
+    let label = diff.key.last().cloned().unwrap_or_default();
+    let status_class = diff.status.class();
+    let body = if !diff.children.is_empty() {
+        let children = diff.children.clone().into_iter().map(|child| rsx! {
+            DiffEntryUI { diff: child }
+        });
+        rsx! {
+            div {
+                class: "attribute-set",
+                { children }
+            }
+        }
+    } else {
+        let old = diff.old.as_ref().map(|node| node.text().to_string());
+        let new = diff.new.as_ref().map(|node| {
+            if syntax::ast::Expr::can_cast(node.kind()) {
+                let ptr = SyntaxNodePtr::new(node);
+                rsx! { ExpressionUI { ptr: ptr, nesting_level: 2 } }
+            } else {
+                let text = node.text().to_string();
+                rsx! { pre { class: "diff-text", "{text}" } }
+            }
+        });
+        rsx! {
+            div {
+                class: "diff-values",
+                if diff.status != DiffStatus::Unchanged {
+                    if let Some(old) = old {
+                        pre { class: "diff-text diff-old", "{old}" }
+                    }
+                }
+                { new }
+            }
+        }
+    };
+    rsx! {
+        div {
+            id: diff.dom_id(),
+            class: "attribute-item diff-entry {status_class}",
+            label {
+                class: "attribute-label",
+                "{label}"
+            }
+            { body }
+        }
+    }
+}
@@ -9,11 +9,11 @@ use closure::closure;
 
 use crate::{use_ast_node_strict};
 use crate::ast::{update_node_value, path_from_root};
+use crate::cursor::{dom_id, move_cursor, Cursor, CursorSource};
 
-use crate::components::attribute_set::AttributeSetUI;
-use crate::components::string_input::StringInput;
-use crate::components::ref_input::RefInput;
-use crate::components::lambda::LambdaUI;
+use crate::components::registry::EditorRegistry;
+use crate::operators::{fit_into_parent, precedence, OPEN_ENDED};
+use crate::options::{option_path_of_value, validate, LoadedOptions, OptionsRoot};
 
 
 fn decide_link_or_element(_node: &SyntaxNode, nesting_level: u16) -> bool {
@@ -34,60 +34,77 @@ fn link_or_element(node: &SyntaxNode, nesting_level: u16, element: Element) -> E
     }
 }
 
-fn can_use_non_fallback_ui(node: &SyntaxNode) -> bool {
-    match_ast! {
+// The branches that can replace an `if` or `assert` when it is unwrapped. An
+// `if` offers both, so unwrapping never drops a branch unasked.
+fn unwrapped(node: &SyntaxNode) -> Vec<(&'static str, SyntaxNode)> {
+    let branches = match_ast! {
         match node {
-        syntax::ast::AttrSet(_) => true,
-        syntax::ast::Lambda(_) => true,
-        syntax::ast::String(_) => true,
-        syntax::ast::Ref(_) => true,
-        _ => false,
+            syntax::ast::IfThenElse(if_else) => vec![
+                ("Unwrap (keep then)", if_else.then_body()),
+                ("Unwrap (keep else)", if_else.else_body()),
+            ],
+            syntax::ast::Assert(assert) => vec![("Unwrap", assert.body())],
+            _ => Vec::new(),
         }
-    }
+    };
+    branches.into_iter()
+        .filter_map(|(label, branch)| Some((label, branch?.syntax().clone())))
+        .collect()
 }
 
 #[component]
 pub fn ExpressionUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
     let ast = use_context::<Signal<SyntaxNode>>();
+    let cursor = use_context::<Signal<Option<Cursor>>>();
     let mut menu_open = use_signal(|| false);
     let node = ptr.read().to_node(&ast.read());
-    let mut fallback_ui = use_signal(|| {
-        !can_use_non_fallback_ui(&node)
+    use_effect(move || {
+        if let Some(c) = cursor.read().as_ref() {
+            if c.source != CursorSource::Graphical && c.ptr == ptr() {
+                document::eval(&format!(
+                    "document.getElementById('{}')?.scrollIntoView({{ block: 'center', behavior: 'smooth' }});",
+                    dom_id(&c.ptr)
+                ));
+            }
+        }
     });
-    let node_ref = node.clone();
+    let is_cursor = cursor.read().as_ref().is_some_and(|c| c.ptr == ptr());
+    let options = use_context::<LoadedOptions>().0;
+    let options_root = use_context::<OptionsRoot>().0;
+    let mismatch = options.read().as_ref().and_then(|options| {
+        let path = option_path_of_value(&node, &options_root.read())?;
+        validate(&node, &options.find(&path)?.option_type).err()
+    });
+    let registry = use_context::<EditorRegistry>();
+    let editor = registry.editor_for(&node);
+    let has_editor = editor.is_some();
+    let mut fallback_ui = use_signal(|| !has_editor);
     let next_level = nesting_level + 1;
-    let body = if fallback_ui() {
-        rsx! { FallbackExpressionUI { ptr:ptr }  }
-    } else { match_ast! {
-        match node_ref {
-            syntax::ast::AttrSet(_) => {
-                link_or_element(&node, nesting_level, rsx! {  AttributeSetUI { ptr:ptr, nesting_level: next_level }  })
-            },
-            syntax::ast::Lambda(_) => {
-                link_or_element(&node, nesting_level, rsx! { LambdaUI { ptr:ptr, nesting_level: next_level }  })
-            },
-            syntax::ast::String(_) => rsx! { StringInput { ptr:ptr } },
-            syntax::ast::Ref(_) => rsx! { RefInput { ptr:ptr } },
-            _ => rsx! { FallbackExpressionUI { ptr:ptr }  },
-        }
-    }};
-    let extra_classes = match_ast! {
-        match node_ref {
-            syntax::ast::AttrSet(_) => if decide_link_or_element(&node, nesting_level) {"atom"} else {"composed"},
-            syntax::ast::Lambda(_) => if decide_link_or_element(&node, nesting_level) {"atom"} else {"composed"},
-            syntax::ast::String(_) => "atom",
-            syntax::ast::Ref(_) => "atom",
-            _ => "atom",
-        }
+    let composed = editor.as_ref().is_some_and(|e| e.composed());
+    let body = match editor.filter(|_| !fallback_ui()) {
+        Some(editor) if editor.composed() => link_or_element(&node, nesting_level, editor.render(ptr, next_level)),
+        Some(editor) => editor.render(ptr, next_level),
+        None => rsx! { FallbackExpressionUI { ptr:ptr }  },
+    };
+    let extra_classes = if composed && !decide_link_or_element(&node, nesting_level) {
+        "composed"
+    } else {
+        "atom"
     };
-    let menu_items = vec![
-        ("Attribute Set", "{}") ,
-        ("Lambda", "{}:{}"),
-        ("String", "\"\""),
-        ("Reference", "ref"),
-    ];
+    let menu_items = registry.templates()
+    .into_iter()
+    .map(|(label, template)| (label, template.to_string()))
+    .chain(once((
+        "Wrap in if",
+        fit_into_parent(&node, format!("if true then {} else null", node.text()), OPEN_ENDED),
+    )))
+    .chain(unwrapped(&node).into_iter().map(|(label, branch)| (
+        label,
+        fit_into_parent(&node, branch.text().to_string(), precedence(&branch)),
+    )))
+    .collect::<Vec<(&str, String)>>();
     let menu_elements: Box<dyn Iterator<Item = Element>> = if fallback_ui() {
-        if can_use_non_fallback_ui(&node) {
+        if has_editor {
             Box::new(once(rsx! {
                 li {
                     onclick: move |_| {
@@ -107,11 +124,11 @@ pub fn ExpressionUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Eleme
             .map(|(label, template)| {
                 rsx! {
                     li { 
-                        onclick: closure!(move mut menu_open, clone node, |_| {
+                        onclick: closure!(move mut menu_open, clone node, clone template, |_| {
                             menu_open.set(false);
                             update_node_value(
                                 node.clone(),
-                                template,
+                                &template,
                                 |syntax| {
                                     <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
                                         .and_then(|sf| sf.expr())
@@ -137,7 +154,14 @@ pub fn ExpressionUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Eleme
     
     rsx! {
         div {
-            class: "expression-ui ".to_owned() + extra_classes,
+            id: dom_id(&ptr()),
+            class: "expression-ui ".to_owned() + extra_classes
+                + if is_cursor { " cursor-node" } else { "" }
+                + if mismatch.is_some() { " invalid-value" } else { "" },
+            onmouseover: move |e| {
+                e.stop_propagation();
+                move_cursor(cursor, ptr(), CursorSource::Graphical);
+            },
             if menu_elements.size_hint().0 > 0 {
                 div {
                     onclick: move |_| {
@@ -158,6 +182,12 @@ pub fn ExpressionUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Eleme
                 }
             }
             { body }
+            if let Some(message) = mismatch {
+                div {
+                    class: "option-mismatch",
+                    "{message}"
+                }
+            }
         }
     }
 }
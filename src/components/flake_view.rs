@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use dioxus::prelude::*;
+use closure::closure;
+
+use crate::ast::{insert_binding, remove_nodes, source_expr, string_literal, update_node_value};
+use crate::components::ExpressionUI;
+use crate::components::lock_graph::LockGraph;
+use crate::flake::{
+    flake_inputs, flake_set, format_date, input_setting, load_lock, non_flake_value, outputs_lambda,
+    short_rev, FlakeInput, FlakeLock, FlakeMode,
+};
+use crate::attrpath::bindings_below;
+use crate::resolve::lambda_formals;
+
+#[component]
+pub fn FlakeView() -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let file_path = use_context::<Signal<PathBuf>>();
+    let mut flake_mode = use_context::<FlakeMode>().0;
+    let mut reloads = use_signal(|| 0u32);
+    let mut new_name = use_signal(String::new);
+    let mut new_url = use_signal(String::new);
+    let lock = use_memo(move || {
+        reloads.read();
+        load_lock(&file_path.read())
+    });
+
+    let Some(set) = flake_set(&ast.read()) else {
+        return rsx! {
+            div {
+                class: "flake-view",
+                "This file is not a flake: it has no top-level attribute set with outputs."
+            }
+        };
+    };
+    let inputs = flake_inputs(&set);
+    let input_names = inputs.iter().map(|input| input.name.clone()).collect::<Vec<String>>();
+
+    let rows = inputs.into_iter().map(|input| {
+        let locked = lock.read().as_ref().ok().cloned();
+        rsx! {
+            FlakeInputRow { input: input, lock: locked }
+        }
+    });
+
+    let outputs = outputs_lambda(&set);
+    let params = outputs.as_ref().and_then(lambda_formals);
+    let param_chips = params.as_ref().map(|(formals, _)| {
+        formals.iter().map(|formal| {
+            let (class, title) = if formal.name == "self" {
+                ("formal supplied", "The flake itself")
+            } else if input_names.contains(&formal.name) {
+                ("formal supplied", "Input of this flake")
+            } else if formal.has_default {
+                ("formal optional", "Not an input, uses its default")
+            } else {
+                ("formal unexpected", "Not an input of this flake")
+            };
+            rsx! {
+                li { class: "{class}", title: "{title}", "{formal.name}" }
+            }
+        }).collect::<Vec<Element>>()
+    }).unwrap_or_default();
+    let unused = params.as_ref()
+        .map(|(formals, _)| input_names.iter()
+            .filter(|name| !formals.iter().any(|formal| &formal.name == *name))
+            .cloned()
+            .collect::<Vec<String>>())
+        .unwrap_or_default();
+    let ellipsis = params.as_ref().is_some_and(|(_, ellipsis)| *ellipsis);
+
+    let locked = lock.read().as_ref().ok().cloned();
+    let lock_status = match &*lock.read() {
+        Ok(lock) => format!("flake.lock: {} nodes", lock.nodes.len()),
+        Err(e) => e.to_string(),
+    };
+
+    let add = closure!(clone set, |_| {
+        let name = new_name().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let (target, key) = input_setting(&set, &name, "url");
+        insert_binding(target, &format!("{key} = {};", string_literal(new_url().trim())));
+        new_name.set(String::new());
+        new_url.set(String::new());
+    });
+
+    rsx! {
+        div {
+            class: "flake-view",
+            div {
+                class: "flake-header",
+                h3 { "Flake" }
+                span { class: "flake-lock-status", "{lock_status}" }
+                button { onclick: move |_| reloads += 1, "Reload lock" }
+                button { onclick: move |_| flake_mode.set(false), "Close" }
+            }
+            h4 { "Inputs" }
+            table {
+                class: "flake-inputs",
+                thead {
+                    tr {
+                        th { "Name" }
+                        th { "URL" }
+                        th { "Follows" }
+                        th { "Flake" }
+                        th { "Locked" }
+                        th {}
+                    }
+                }
+                tbody {
+                    { rows }
+                    tr {
+                        td {
+                            input {
+                                class: "simple-input",
+                                placeholder: "name",
+                                value: new_name(),
+                                oninput: move |e| new_name.set(e.value()),
+                            }
+                        }
+                        td {
+                            input {
+                                class: "simple-input",
+                                placeholder: "github:owner/repo",
+                                value: new_url(),
+                                oninput: move |e| new_url.set(e.value()),
+                            }
+                        }
+                        td {}
+                        td {}
+                        td {}
+                        td {
+                            button { onclick: add, "Add input" }
+                        }
+                    }
+                }
+            }
+            h4 { "Outputs" }
+            if outputs.is_none() {
+                div { class: "flake-warning", "outputs is not a function" }
+            } else if params.is_none() {
+                div { class: "flake-warning", "outputs takes its inputs as a single argument" }
+            } else {
+                div {
+                    class: "apply-formals",
+                    ul {
+                        { param_chips.into_iter() }
+                        if ellipsis {
+                            li { class: "formal optional", "..." }
+                        }
+                    }
+                }
+                if !unused.is_empty() {
+                    div {
+                        class: "flake-unused",
+                        if ellipsis {
+                            "Not taken as parameters, reachable through @-patterns only: {unused.join(\", \")}"
+                        } else {
+                            "Inputs missing from the outputs parameters: {unused.join(\", \")}"
+                        }
+                    }
+                }
+            }
+            if let Some(locked) = locked {
+                h4 { "Lock graph" }
+                LockGraph { lock: locked }
+            }
+        }
+    }
+}
+
+#[component]
+fn FlakeInputRow(input: FlakeInput, lock: Option<FlakeLock>) -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let locked = lock.as_ref().and_then(|lock| lock.resolve(std::slice::from_ref(&input.name)).cloned());
+    let name = input.name.clone();
+    let remove = closure!(clone name, |_| {
+        let Some(set) = flake_set(&ast.read()) else {
+            return;
+        };
+        let bindings = bindings_below(&set, &["inputs".to_string(), name.clone()]);
+        remove_nodes(&bindings);
+    });
+    let shorthand_url = input.url.clone().filter(|_| input.shorthand);
+    let mark_non_flake = closure!(clone name, |_| {
+        if let Some(url) = shorthand_url.clone() {
+            update_node_value(url.clone(), &non_flake_value(&url), source_expr);
+            return;
+        }
+        let Some(set) = flake_set(&ast.read()) else {
+            return;
+        };
+        let (target, key) = input_setting(&set, &name, "flake");
+        insert_binding(target, &format!("{key} = false;"));
+    });
+    let follows = input.follows.as_ref()
+        .map(|follows| format!("→ {}", follows.text()))
+        .into_iter()
+        .chain(input.input_follows.iter().map(|(sub, target)| format!("{sub} → {}", target.text())))
+        .collect::<Vec<String>>();
+    rsx! {
+        tr {
+            td { class: "flake-input-name", "{input.name}" }
+            td {
+                if let Some(url) = input.url.as_ref() {
+                    ExpressionUI { ptr: SyntaxNodePtr::new(url), nesting_level: 2 }
+                }
+            }
+            td {
+                for line in follows {
+                    div { class: "flake-follows", "{line}" }
+                }
+            }
+            td {
+                if let Some(flake) = input.flake.as_ref() {
+                    ExpressionUI { ptr: SyntaxNodePtr::new(flake), nesting_level: 2 }
+                } else {
+                    button {
+                        title: "Add flake = false for inputs without a flake.nix",
+                        onclick: mark_non_flake,
+                        "flake = false"
+                    }
+                }
+            }
+            td {
+                if let Some(locked) = locked {
+                    span {
+                        class: "flake-locked",
+                        title: "{locked.source}",
+                        "{locked.rev.as_deref().map(short_rev).unwrap_or(\"-\")}"
+                    }
+                    if let Some(date) = locked.last_modified.map(format_date) {
+                        span { class: "flake-date", "{date}" }
+                    }
+                } else {
+                    span { class: "flake-unlocked", "not locked" }
+                }
+            }
+            td {
+                button {
+                    class: "attrpath-action",
+                    title: "Remove input",
+                    onclick: remove,
+                    "✕"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::PathBuf;
+use syntax::SyntaxNode;
+use dioxus::prelude::*;
+
+use crate::diff::DiffStatus;
+use crate::git::{commit_file, load_head, GitHead, HeadDiff};
+
+#[component]
+pub fn GitPanel() -> Element {
+    let file_path = use_context::<Signal<PathBuf>>();
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let mut head = use_context::<Signal<Option<GitHead>>>();
+    let head_diff = use_context::<HeadDiff>().0;
+    let mut message = use_signal(String::new);
+    let mut status = use_signal(|| None::<String>);
+
+    let mut reload = move || {
+        match load_head(&file_path.read()) {
+            Ok(loaded) => {
+                head.set(Some(loaded));
+            },
+            Err(e) => {
+                head.set(None);
+                status.set(Some(e.to_string()));
+            },
+        }
+    };
+    use_effect(move || {
+        status.set(None);
+        reload();
+    });
+    use_drop(move || head.set(None));
+
+    let changes = head_diff.read().iter()
+        .flat_map(|diff| diff.changes())
+        .filter(|diff| diff.status != DiffStatus::Unchanged)
+        .count();
+    let summary = if head.read().is_some() {
+        format!("{changes} changed bindings compared to HEAD")
+    } else {
+        "No committed version".to_string()
+    };
+
+    rsx! {
+        div {
+            class: "git-panel",
+            span { class: "git-summary", "{summary}" }
+            input {
+                class: "git-message simple-input",
+                placeholder: "Commit message",
+                value: message(),
+                oninput: move |e| message.set(e.value()),
+            }
+            button {
+                disabled: message.read().trim().is_empty(),
+                onclick: move |_| {
+                    let path = file_path.read().clone();
+                    // The whole file is written so header comments are committed too.
+                    if let Err(e) = fs::write(&path, ast.read().to_string()) {
+                        status.set(Some(e.to_string()));
+                        return;
+                    }
+                    match commit_file(&path, &message.read()) {
+                        Ok(_) => {
+                            message.set(String::new());
+                            status.set(Some("Committed".to_string()));
+                            reload();
+                        },
+                        Err(e) => status.set(Some(e.to_string())),
+                    }
+                },
+                "Stage & Commit"
+            }
+            if let Some(status) = status() {
+                span { class: "git-status", "{status}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use ide::{AnalysisHost, FileId};
+use syntax::{SyntaxKind, SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+use closure::closure;
+use declaro_core::scope::get_bindings_in_scope;
+
+use crate::components::ExpressionUI;
+use crate::components::notes::{MoveButtons, NotesUI};
+use crate::{use_ast_node_strict};
+use crate::ast::{attr_key, attr_text, inherit_source, inherit_text, line_indent, remove_node, replace_with_bindings};
+use crate::resolve::is_rec;
+
+// `inherit x;` means `x = x;` only where the binding itself isn't in scope,
+// otherwise the binding would refer to itself.
+fn self_scoped(binding: &SyntaxNode) -> bool {
+    binding.parent().is_some_and(|parent| match parent.kind() {
+        SyntaxKind::ATTR_SET => is_rec(&parent),
+        SyntaxKind::LET_IN => true,
+        _ => false,
+    })
+}
+
+/// Whether `x = x;` can be turned into `inherit x;`.
+pub fn can_inherit(attr: &syntax::ast::AttrpathValue) -> bool {
+    let Some(attrs) = attr.attrpath().map(|ap| ap.attrs().collect::<Vec<_>>()) else {
+        return false;
+    };
+    let value = attr.value().and_then(|v| match v {
+        syntax::ast::Expr::Ref(reference) => reference.token().map(|t| t.text().to_string()),
+        _ => None,
+    });
+    attrs.len() == 1
+        && matches!(attrs[0], syntax::ast::Attr::Name(_))
+        && value == Some(attr_key(&attrs[0]))
+        && !self_scoped(attr.syntax())
+}
+
+pub fn to_inherit(attr: &syntax::ast::AttrpathValue) {
+    let Some(name) = attr.attrpath().and_then(|ap| ap.attrs().next()).map(|a| attr_key(&a)) else {
+        return;
+    };
+    replace_with_bindings(attr.syntax().clone(), &inherit_text(None, &[name]));
+}
+
+#[component]
+pub fn InheritUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let inherit = use_ast_node_strict!(ptr => syntax::ast::Inherit);
+    let mut new_name = use_signal(String::new);
+    let node = inherit.read().syntax().clone();
+    let names = inherit.read().attrs().map(|attr| attr_key(&attr)).collect::<Vec<String>>();
+    let source = inherit_source(&node);
+    let source_text = source.as_ref().map(|paren| paren.text().to_string());
+    let source_ptr = source.as_ref()
+        .and_then(|paren| paren.children().next())
+        .map(|expr| SyntaxNodePtr::new(&expr));
+    let convertible = !self_scoped(&node);
+    let analysis = use_context::<Signal<(AnalysisHost, FileId)>>();
+    // The source is evaluated in the scope of the set or `let` holding the inherit.
+    let mut sources = node.parent()
+        .and_then(|scope| get_bindings_in_scope(&scope, &analysis.read()).ok())
+        .unwrap_or_default();
+    sources.sort();
+    sources.dedup();
+
+    let chips = names.iter().enumerate().map(|(i, name)| {
+        let remove = closure!(clone node, clone names, clone source_text, |_| {
+            let mut remaining = names.clone();
+            remaining.remove(i);
+            if remaining.is_empty() {
+                remove_node(node.clone());
+            } else {
+                replace_with_bindings(node.clone(), &inherit_text(source_text.as_deref(), &remaining));
+            }
+        });
+        let convert = closure!(clone node, clone names, clone source_text, clone name, |_| {
+            let mut remaining = names.clone();
+            remaining.remove(i);
+            let value = match source_text.as_deref() {
+                Some(source) => format!("{source}.{}", attr_text(&name)),
+                None => attr_text(&name),
+            };
+            let binding = format!("{} = {value};", attr_text(&name));
+            let text = if remaining.is_empty() {
+                binding
+            } else {
+                let indent = line_indent(&node);
+                format!("{}\n{indent}{binding}", inherit_text(source_text.as_deref(), &remaining))
+            };
+            replace_with_bindings(node.clone(), &text);
+        });
+        rsx! {
+            li {
+                class: "inherit-chip",
+                "{name}"
+                if convertible {
+                    button {
+                        class: "attrpath-action",
+                        title: "Convert to a binding",
+                        onclick: convert,
+                        "="
+                    }
+                }
+                button {
+                    class: "attrpath-action",
+                    title: "Remove name",
+                    onclick: remove,
+                    "✕"
+                }
+            }
+        }
+    });
+
+    let add = closure!(clone node, clone names, clone source_text, |_| {
+        let name = new_name().trim().to_string();
+        if name.is_empty() || names.contains(&name) {
+            return;
+        }
+        let mut extended = names.clone();
+        extended.push(name);
+        new_name.set(String::new());
+        replace_with_bindings(node.clone(), &inherit_text(source_text.as_deref(), &extended));
+    });
+    let remove_source = closure!(clone node, clone names, |_| {
+        replace_with_bindings(node.clone(), &inherit_text(None, &names));
+    });
+    let set_source = closure!(clone node, clone names, |e: Event<FormData>| {
+        let name = e.value();
+        if !name.is_empty() {
+            replace_with_bindings(node.clone(), &inherit_text(Some(&format!("({name})")), &names));
+        }
+    });
+    let source_options = sources.iter().map(|name| rsx! {
+        option { value: name.clone(), "{name}" }
+    });
+
+    rsx! {
+        NotesUI { ptr: ptr }
+        div {
+            class: "attribute-item inherit-item",
+            MoveButtons { node: node.clone() }
+            label {
+                class: "attribute-label",
+                "inherit"
+            }
+            div {
+                class: "inherit-source",
+                if let Some(source_ptr) = source_ptr {
+                    span { "from" }
+                    ExpressionUI { ptr: source_ptr, nesting_level: nesting_level }
+                }
+                if source_text.is_some() {
+                    button {
+                        class: "inherit-toggle-source",
+                        onclick: remove_source,
+                        "Remove source"
+                    }
+                } else {
+                    select {
+                        class: "inherit-toggle-source",
+                        onchange: set_source,
+                        option { value: "", selected: true, "Inherit from…" }
+                        { source_options }
+                    }
+                }
+            }
+            ul {
+                class: "inherit-names",
+                { chips }
+                li {
+                    input {
+                        class: "inherit-new-name simple-input",
+                        placeholder: "name",
+                        value: new_name(),
+                        oninput: move |e| new_name.set(e.value()),
+                    }
+                    button {
+                        class: "attrpath-action",
+                        title: "Add name",
+                        onclick: add,
+                        "+"
+                    }
+                }
+            }
+        }
+    }
+}
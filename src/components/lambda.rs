@@ -3,6 +3,7 @@ use syntax::ast::AstNode;
 use dioxus::prelude::*;
 
 use crate::components::ExpressionUI;
+use crate::components::notes::NotesUI;
 use crate::use_ast_node_strict;
 
 #[component]
@@ -12,8 +13,12 @@ pub fn LambdaUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
 
     let param_elements = params.map(|param| {
         let label = param.syntax().text().to_string();
+        let param_ptr = SyntaxNodePtr::new(param.syntax());
         rsx! {
-            li { "{label}" }
+            li {
+                NotesUI { ptr: param_ptr }
+                "{label}"
+            }
         }
     });
 
@@ -0,0 +1,81 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+use closure::closure;
+
+use crate::components::ExpressionUI;
+use crate::components::notes::{MoveButtons, NotesUI};
+use crate::components::package_picker::PackagePicker;
+use crate::{use_ast_node_strict};
+use crate::ast::{remove_node, update_node_value};
+
+fn append_element(list: &SyntaxNode, element: &str) {
+    let text = list.text().to_string();
+    let Some(open) = text.strip_suffix(']') else {
+        return;
+    };
+    update_node_value(
+        list.clone(),
+        &format!("{} {element} ]", open.trim_end()),
+        |syntax| {
+            <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                .and_then(|sf| sf.expr())
+                .map(|expr| expr.syntax().clone())
+        }
+    );
+}
+
+#[component]
+pub fn ListUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let list = use_ast_node_strict!(ptr => syntax::ast::List);
+    let mut picking = use_signal(|| false);
+    let node = list.read().syntax().clone();
+
+    let elements = node.children().map(|element| {
+        let element_ptr = SyntaxNodePtr::new(&element);
+        let remove = closure!(clone element, |_| remove_node(element.clone()));
+        rsx! {
+            div {
+                class: "list-item",
+                NotesUI { ptr: element_ptr }
+                div {
+                    class: "list-element",
+                    MoveButtons { node: element.clone() }
+                    ExpressionUI { ptr: element_ptr, nesting_level: nesting_level }
+                    button {
+                        class: "attrpath-action",
+                        title: "Remove element",
+                        onclick: remove,
+                        "✕"
+                    }
+                }
+            }
+        }
+    });
+
+    let add = closure!(clone node, |_| append_element(&node, "null"));
+    let pick = closure!(clone node, |reference: String| {
+        picking.set(false);
+        append_element(&node, &reference);
+    });
+
+    rsx! {
+        div {
+            class: "list-node",
+            { elements }
+            button {
+                class: "list-add",
+                onclick: add,
+                "Add element"
+            }
+            button {
+                class: "list-add",
+                onclick: move |_| picking.set(!picking()),
+                if picking() { "Close packages" } else { "Add package…" }
+            }
+            if picking() {
+                PackagePicker { at: node.clone(), on_pick: pick }
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeSet;
+use syntax::SyntaxNode;
+use dioxus::prelude::*;
+use closure::closure;
+
+use crate::ast::{insert_binding, string_literal};
+use crate::flake::{
+    flake_inputs, flake_set, format_date, input_setting, short_rev, FlakeLock, FollowsSuggestion,
+    LockInput,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+enum EdgeTarget {
+    Node { key: String, source: String, rev: Option<String>, last_modified: Option<i64>, duplicate: bool },
+    Follows(String),
+    Missing(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Edge {
+    depth: usize,
+    name: String,
+    target: EdgeTarget,
+}
+
+// The lock graph as an indented tree of input edges, starting at the root.
+// Nodes already on the current branch are not expanded again.
+fn edges(
+    lock: &FlakeLock,
+    duplicates: &BTreeSet<String>,
+    key: &str,
+    depth: usize,
+    branch: &mut Vec<String>,
+    out: &mut Vec<Edge>,
+) {
+    let Some(node) = lock.nodes.get(key) else {
+        return;
+    };
+    branch.push(key.to_string());
+    for (name, input) in &node.inputs {
+        match input {
+            LockInput::Follows(path) => out.push(Edge {
+                depth,
+                name: name.clone(),
+                target: EdgeTarget::Follows(path.join("/")),
+            }),
+            LockInput::Node(target) => match lock.nodes.get(target) {
+                Some(target_node) => {
+                    out.push(Edge {
+                        depth,
+                        name: name.clone(),
+                        target: EdgeTarget::Node {
+                            key: target.clone(),
+                            source: target_node.source.clone(),
+                            rev: target_node.rev.clone(),
+                            last_modified: target_node.last_modified,
+                            duplicate: duplicates.contains(target),
+                        },
+                    });
+                    if !branch.contains(target) {
+                        edges(lock, duplicates, target, depth + 1, branch, out);
+                    }
+                },
+                None => out.push(Edge { depth, name: name.clone(), target: EdgeTarget::Missing(target.clone()) }),
+            },
+        }
+    }
+    branch.pop();
+}
+
+// Suggestions that flake.nix doesn't already contain.
+fn open_suggestions(lock: &FlakeLock, root: &SyntaxNode) -> Vec<FollowsSuggestion> {
+    let inputs = flake_set(root).map(|set| flake_inputs(&set)).unwrap_or_default();
+    lock.follows_suggestions().into_iter()
+        .filter(|suggestion| !inputs.iter().any(|input| {
+            input.name == suggestion.input
+                && input.input_follows.iter().any(|(nested, _)| nested == &suggestion.nested)
+        }))
+        .collect()
+}
+
+#[component]
+pub fn LockGraph(lock: FlakeLock) -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let mut rows = Vec::new();
+    edges(&lock, &lock.duplicate_keys(), &lock.root, 0, &mut Vec::new(), &mut rows);
+    let duplicates = lock.duplicates();
+    let suggestions = open_suggestions(&lock, &ast.read());
+
+    let rows = rows.into_iter().map(|edge| {
+        let indent = format!("padding-left: {}em", edge.depth * 2);
+        let target = match edge.target {
+            EdgeTarget::Node { key, source, rev, last_modified, duplicate } => rsx! {
+                span {
+                    class: if duplicate { "lock-node lock-duplicate" } else { "lock-node" },
+                    title: if duplicate { "The same source is locked more than once" } else { "" },
+                    "{key}"
+                }
+                span { class: "lock-source", "{source}" }
+                span { class: "flake-locked", "{rev.as_deref().map(short_rev).unwrap_or(\"-\")}" }
+                if let Some(date) = last_modified.map(format_date) {
+                    span { class: "flake-date", "{date}" }
+                }
+            },
+            EdgeTarget::Follows(path) => rsx! {
+                span { class: "flake-follows", "follows {path}" }
+            },
+            EdgeTarget::Missing(key) => rsx! {
+                span { class: "flake-unlocked", "missing node {key}" }
+            },
+        };
+        rsx! {
+            li {
+                class: "lock-edge",
+                style: "{indent}",
+                span { class: "flake-input-name", "{edge.name}" }
+                { target }
+            }
+        }
+    });
+
+    let suggestion_items = suggestions.into_iter().map(|suggestion| {
+        let label = format!(
+            "inputs.{}.inputs.{}.follows = \"{}\"",
+            suggestion.input, suggestion.nested, suggestion.target
+        );
+        rsx! {
+            li {
+                class: "lock-suggestion",
+                code { "{label}" }
+                button {
+                    onclick: closure!(clone suggestion, |_| {
+                        let Some(set) = flake_set(&ast.read()) else {
+                            return;
+                        };
+                        let (target, key) = input_setting(&set, &suggestion.input, &suggestion.setting());
+                        insert_binding(target, &format!("{key} = {};", string_literal(&suggestion.target)));
+                    }),
+                    "Add follows"
+                }
+            }
+        }
+    }).collect::<Vec<Element>>();
+
+    rsx! {
+        div {
+            class: "lock-graph",
+            ul {
+                class: "lock-edges",
+                { rows }
+            }
+            if !duplicates.is_empty() {
+                div {
+                    class: "flake-warning",
+                    "Locked more than once: "
+                    {duplicates.iter().map(|keys| keys.join(" = ")).collect::<Vec<String>>().join(", ")}
+                }
+            }
+            if !suggestion_items.is_empty() {
+                h4 { "Suggested follows" }
+                ul {
+                    class: "lock-suggestions",
+                    { suggestion_items.into_iter() }
+                }
+                div {
+                    class: "flake-lock-status",
+                    "Run nix flake lock afterwards to update flake.lock."
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use syntax::{SyntaxNode, SyntaxNodePtr, SyntaxToken};
+use dioxus::prelude::*;
+use closure::closure;
+
+use crate::comments::{
+    comment_text, leading_comments, trailing_comment, with_comment_text, with_leading_comment,
+    with_moved,
+};
+
+#[component]
+fn NoteInput(comment: SyntaxToken, trailing: bool) -> Element {
+    let mut ast = use_context::<Signal<SyntaxNode>>();
+    let text = comment_text(&comment);
+    rsx! {
+        textarea {
+            class: if trailing { "note trailing simple-input" } else { "note simple-input" },
+            rows: text.lines().count().max(1),
+            value: text,
+            onchange: move |e| ast.set(with_comment_text(&comment, &e.value())),
+        }
+    }
+}
+
+/// The comments attached to a binding, list element or parameter, editable as
+/// notes. Clearing a note removes the comment.
+#[component]
+pub fn NotesUI(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
+    let mut ast = use_context::<Signal<SyntaxNode>>();
+    let mut adding = use_signal(|| false);
+    let node = ptr.read().to_node(&ast.read());
+    let leading = leading_comments(&node).into_iter().map(|comment| {
+        rsx! { NoteInput { comment: comment, trailing: false } }
+    });
+    let trailing = trailing_comment(&node);
+    let add = closure!(clone node, |e: Event<FormData>| {
+        adding.set(false);
+        if e.value().trim().is_empty() {
+            return;
+        }
+        if let Ok(new_root) = with_leading_comment(&node, &e.value()) {
+            ast.set(new_root);
+        }
+    });
+    rsx! {
+        div {
+            class: "notes",
+            { leading }
+            if let Some(comment) = trailing {
+                NoteInput { comment: comment, trailing: true }
+            }
+            if adding() {
+                textarea {
+                    class: "note simple-input",
+                    placeholder: "New comment",
+                    onchange: add,
+                }
+            } else {
+                button {
+                    class: "attrpath-action",
+                    title: "Add comment",
+                    onclick: move |_| adding.set(true),
+                    "#"
+                }
+            }
+        }
+    }
+}
+
+/// Buttons moving a node past its neighbours, comments included.
+#[component]
+pub fn MoveButtons(node: SyntaxNode) -> Element {
+    let mut ast = use_context::<Signal<SyntaxNode>>();
+    let can_move_up = node.prev_sibling().is_some();
+    let can_move_down = node.next_sibling().is_some();
+    let move_by = move |up: bool| {
+        if let Ok(new_root) = with_moved(&node, up) {
+            ast.set(new_root);
+        }
+    };
+    let mut move_up = move_by.clone();
+    let mut move_down = move_by;
+    rsx! {
+        button {
+            class: "attrpath-action",
+            title: "Move up",
+            disabled: !can_move_up,
+            onclick: move |_| move_up(true),
+            "↑"
+        }
+        button {
+            class: "attrpath-action",
+            title: "Move down",
+            disabled: !can_move_down,
+            onclick: move |_| move_down(false),
+            "↓"
+        }
+    }
+}
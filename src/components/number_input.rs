@@ -0,0 +1,101 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::{AstNode, LiteralKind};
+use dioxus::prelude::*;
+
+use crate::ast::{update_node_value};
+use crate::operators::operator_token;
+
+pub fn is_number(literal: &syntax::ast::Literal) -> bool {
+    matches!(literal.kind(), Some(LiteralKind::Int) | Some(LiteralKind::Float))
+}
+
+// Negative numbers are a negation of a number literal in the syntax tree.
+fn number_literal(node: &SyntaxNode) -> Option<syntax::ast::Literal> {
+    let literal = match syntax::ast::UnaryOp::cast(node.clone()) {
+        Some(op) if operator_token(op.syntax()).as_deref() == Some("-") => {
+            syntax::ast::Literal::cast(op.syntax().children().next()?)?
+        },
+        Some(_) => return None,
+        None => syntax::ast::Literal::cast(node.clone())?,
+    };
+    is_number(&literal).then_some(literal)
+}
+
+pub fn is_number_node(node: &SyntaxNode) -> bool {
+    number_literal(node).is_some()
+}
+
+// Only accepts input that the parser reads back as a single, optionally
+// negated number literal, so `1.`, `1e5` or `-1` are valid while `1.2.3` is
+// rejected.
+fn parse_number(value: &str) -> Option<LiteralKind> {
+    let value = value.trim();
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let parsed = syntax::parse_file(digits).syntax_node();
+    let literal = <syntax::ast::SourceFile as AstNode>::cast(parsed)
+        .and_then(|sf| sf.expr())
+        .and_then(|expr| match expr {
+            syntax::ast::Expr::Literal(literal) => Some(literal),
+            _ => None,
+        })?;
+    if literal.syntax().text() != digits {
+        return None;
+    }
+    literal.kind().filter(|kind| matches!(kind, LiteralKind::Int | LiteralKind::Float))
+}
+
+#[component]
+pub fn NumberInput(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let node = use_memo(move || ptr.read().to_node(&ast.read()));
+    let value = node.read().text().to_string();
+    let is_float = number_literal(&node.read())
+        .is_some_and(|literal| matches!(literal.kind(), Some(LiteralKind::Float)));
+    let mut draft = use_signal(|| None::<String>);
+    let invalid = draft.read().is_some();
+    let shown = draft().unwrap_or(value.clone());
+    let write = move |value: &str| {
+        update_node_value(
+            node.read().clone(),
+            value,
+            |syntax| {
+                <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                    .and_then(|sf| sf.expr())
+                    .map(|expr| expr.syntax().clone())
+            }
+        );
+    };
+    let converted = if is_float {
+        value.parse::<f64>().map(|f| format!("{}", f.trunc() as i64)).unwrap_or("0".to_string())
+    } else {
+        format!("{value}.0")
+    };
+    rsx! {
+        span {
+            class: "number-input-wrapper",
+            input {
+                class: if invalid { "number-input simple-input invalid" } else { "number-input simple-input" },
+                inputmode: "decimal",
+                value: shown,
+                oninput: move |e| {
+                    let value = e.value();
+                    if parse_number(&value).is_some() {
+                        draft.set(None);
+                        write(value.trim());
+                    } else {
+                        draft.set(Some(value));
+                    }
+                }
+            }
+            button {
+                class: "number-kind",
+                title: if is_float { "Convert to integer" } else { "Convert to float" },
+                onclick: move |_| {
+                    draft.set(None);
+                    write(&converted);
+                },
+                if is_float { "float" } else { "int" }
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+use closure::closure;
+
+use crate::components::ExpressionUI;
+use crate::{use_ast_node_strict};
+use crate::ast::{update_node_value};
+use crate::operators::{
+    binary_info, fit_into_parent, flatten_chain, join_operands, operator_token, precedence,
+    Assoc, BINARY_OPERATORS, UNARY_OPERATORS,
+};
+
+fn replace_operation(node: &SyntaxNode, text: String, precedence: u8) {
+    update_node_value(
+        node.clone(),
+        &fit_into_parent(node, text, precedence),
+        |syntax| {
+            <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                .and_then(|sf| sf.expr())
+                .map(|expr| expr.syntax().clone())
+        }
+    );
+}
+
+fn rewrite_chain(node: &SyntaxNode, operands: &[(String, u8)], op: &str) {
+    match operands {
+        [single] => replace_operation(node, single.0.clone(), single.1),
+        _ => {
+            let precedence = binary_info(op).map(|(precedence, _)| precedence).unwrap_or_default();
+            replace_operation(node, join_operands(operands, op), precedence);
+        },
+    }
+}
+
+fn operand_template(op: &str) -> &'static str {
+    match op {
+        "//" => "{ }",
+        "++" => "[ ]",
+        "&&" | "||" | "->" => "true",
+        _ => "0",
+    }
+}
+
+#[component]
+pub fn BinaryOpUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let operation = use_ast_node_strict!(ptr => syntax::ast::BinaryOp);
+    let node = operation.read().syntax().clone();
+    let op = operator_token(&node).unwrap_or_default();
+    let operands = flatten_chain(&node);
+    let texts = operands.iter()
+        .map(|operand| (operand.text().to_string(), precedence(operand)))
+        .collect::<Vec<(String, u8)>>();
+    let chain = operands.len() > 2;
+    let extendable = binary_info(&op).is_some_and(|(_, assoc)| assoc != Assoc::None);
+
+    let elements = operands.iter().enumerate().map(|(i, operand)| {
+        let operand_ptr = SyntaxNodePtr::new(operand);
+        let options = BINARY_OPERATORS.iter().map(|(symbol, _, assoc)| {
+            rsx! {
+                option {
+                    value: *symbol,
+                    selected: *symbol == op,
+                    // Comparisons can't be chained without parentheses.
+                    disabled: chain && *assoc == Assoc::None,
+                    "{symbol}"
+                }
+            }
+        });
+        let change = closure!(clone node, clone texts, |e: Event<FormData>| {
+            rewrite_chain(&node, &texts, &e.value());
+        });
+        let remove = closure!(clone node, clone texts, clone op, |_| {
+            let mut remaining = texts.clone();
+            remaining.remove(i);
+            rewrite_chain(&node, &remaining, &op);
+        });
+        rsx! {
+            if i > 0 {
+                select {
+                    class: "operator-select",
+                    onchange: change,
+                    { options }
+                }
+            }
+            div {
+                class: "operator-operand",
+                ExpressionUI { ptr: operand_ptr, nesting_level: nesting_level }
+                if chain {
+                    button {
+                        class: "operator-remove",
+                        title: "Remove operand",
+                        onclick: remove,
+                        "✕"
+                    }
+                }
+            }
+        }
+    });
+
+    let add = closure!(clone node, clone texts, clone op, |_| {
+        let mut extended = texts.clone();
+        extended.push((operand_template(&op).to_string(), 0));
+        rewrite_chain(&node, &extended, &op);
+    });
+
+    rsx! {
+        div {
+            class: "operator-node",
+            { elements }
+            if extendable {
+                button {
+                    class: "operator-add",
+                    onclick: add,
+                    "Add operand"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn UnaryOpUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let operation = use_ast_node_strict!(ptr => syntax::ast::UnaryOp);
+    let node = operation.read().syntax().clone();
+    let op = operator_token(&node).unwrap_or_default();
+    let Some(operand) = node.children().next() else {
+        return rsx! { FallbackOperand {} };
+    };
+    let operand_ptr = SyntaxNodePtr::new(&operand);
+    let operand_text = operand.text().to_string();
+    let operand_precedence = precedence(&operand);
+
+    let options = UNARY_OPERATORS.iter().map(|(symbol, _)| {
+        rsx! {
+            option {
+                value: *symbol,
+                selected: *symbol == op,
+                "{symbol}"
+            }
+        }
+    });
+    let change = closure!(clone node, clone operand_text, |e: Event<FormData>| {
+        let new_op = e.value();
+        let Some((_, new_precedence)) = UNARY_OPERATORS.iter().find(|(symbol, _)| *symbol == new_op) else {
+            return;
+        };
+        let operand = if operand_precedence > *new_precedence {
+            format!("({operand_text})")
+        } else {
+            operand_text.clone()
+        };
+        replace_operation(&node, format!("{new_op}{operand}"), *new_precedence);
+    });
+    let unwrap = closure!(clone node, clone operand_text, |_| {
+        replace_operation(&node, operand_text.clone(), operand_precedence);
+    });
+
+    rsx! {
+        div {
+            class: "operator-node unary",
+            select {
+                class: "operator-select",
+                onchange: change,
+                { options }
+            }
+            div {
+                class: "operator-operand",
+                ExpressionUI { ptr: operand_ptr, nesting_level: nesting_level }
+            }
+            button {
+                class: "operator-remove",
+                title: "Remove operator",
+                onclick: unwrap,
+                "✕"
+            }
+        }
+    }
+}
+
+#[component]
+fn FallbackOperand() -> Element {
+    rsx! {
+        span { class: "operator-missing", "missing operand" }
+    }
+}
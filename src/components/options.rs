@@ -0,0 +1,133 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::{AstNode, HasStringParts};
+use dioxus::prelude::*;
+use declaro_core::document::parse_attrpath;
+
+use crate::{use_ast_node_strict};
+use crate::ast::{insert_binding, string_literal, update_node_value};
+use crate::attrpath::parse_attrpath_input;
+use crate::options::{LoadedOptions, OptionDecl, OptionType};
+
+/// The values of an enum option, also when it may be null.
+pub fn enum_values(option_type: &OptionType) -> Option<Vec<String>> {
+    match option_type {
+        OptionType::Enum(values) => Some(values.clone()),
+        OptionType::NullOr(inner) => enum_values(inner),
+        _ => None,
+    }
+}
+
+#[component]
+pub fn OptionInfo(option: OptionDecl) -> Element {
+    rsx! {
+        details {
+            class: "option-info",
+            summary {
+                span { class: "option-type", "{option.type_description}" }
+                if option.read_only {
+                    span { class: "option-read-only", "read-only" }
+                }
+            }
+            if !option.description.is_empty() {
+                p { class: "option-description", "{option.description}" }
+            }
+            if let Some(default) = option.default.as_ref() {
+                div {
+                    class: "option-value",
+                    span { "Default: " }
+                    code { "{default}" }
+                }
+            }
+            if let Some(example) = option.example.as_ref() {
+                div {
+                    class: "option-value",
+                    span { "Example: " }
+                    code { "{example}" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn EnumInput(ptr: ReadSignal<SyntaxNodePtr>, values: Vec<String>) -> Element {
+    let node = use_ast_node_strict!(ptr => syntax::ast::String);
+    let current = node.read().string_parts().filter_map(|part| match part {
+        syntax::ast::StringPart::Fragment(text) => Some(text.text().to_string()),
+        _ => None,
+    }).collect::<String>();
+    let known = values.contains(&current);
+    rsx! {
+        select {
+            class: "enum-input simple-input",
+            onchange: move |e| {
+                update_node_value(
+                    node.read().syntax().clone(),
+                    &string_literal(&e.value()),
+                    |syntax| {
+                        <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                            .and_then(|sf| sf.expr())
+                            .map(|expr| expr.syntax().clone())
+                    }
+                );
+            },
+            if !known {
+                option { value: "{current}", selected: true, "{current}" }
+            }
+            for value in values {
+                option {
+                    value: "{value}",
+                    selected: value == current,
+                    "{value}"
+                }
+            }
+        }
+    }
+}
+
+/// Adds a binding for an option below `prefix`, with a value that matches the
+/// option type.
+#[component]
+pub fn AddOption(set: SyntaxNode, prefix: Vec<String>) -> Element {
+    let options = use_context::<LoadedOptions>().0;
+    let mut name = use_signal(String::new);
+    let list_id = format!("options-{}", prefix.join("-"));
+    let completions = options.read().as_ref()
+        .map(|options| options.completions(&prefix, &name()))
+        .unwrap_or_default();
+    let add = move |_| {
+        let path = name().trim().to_string();
+        if path.is_empty() {
+            return;
+        }
+        let mut full = prefix.clone();
+        full.extend(parse_attrpath(&path).unwrap_or_default());
+        let value = options.read().as_ref()
+            .and_then(|options| options.find(&full).map(|option| option.option_type.template()))
+            .unwrap_or_else(|| "null".to_string());
+        insert_binding(set.clone(), &format!("{} = {value};", parse_attrpath_input(&path)));
+        name.set(String::new());
+    };
+    rsx! {
+        div {
+            class: "add-option",
+            input {
+                class: "simple-input",
+                list: "{list_id}",
+                placeholder: "option",
+                value: name(),
+                oninput: move |e| name.set(e.value()),
+            }
+            datalist {
+                id: "{list_id}",
+                for completion in completions {
+                    option { value: "{completion}" }
+                }
+            }
+            button {
+                onclick: add,
+                "Add option"
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use ide::{AnalysisHost, FileId, SymbolTree};
+use syntax::{SyntaxKind, SyntaxNode};
+use rowan::TextRange;
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use crate::ast::{path_from_root, AstPath};
+use crate::router::Route;
+
+#[derive(Clone, Copy)]
+pub struct OutlineVisible(pub Signal<bool>);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineEntry {
+    pub name: String,
+    pub target: Option<AstPath>,
+    pub children: Vec<OutlineEntry>,
+}
+
+impl OutlineEntry {
+    fn contains(&self, path: &AstPath) -> bool {
+        self.target.as_ref() == Some(path)
+            || self.children.iter().any(|child| child.contains(path))
+    }
+}
+
+pub fn document_outline(root: &SyntaxNode, analysis: &(AnalysisHost, FileId)) -> Vec<OutlineEntry> {
+    analysis.0.snapshot()
+        .symbol_hierarchy(analysis.1)
+        .map(|symbols| symbols.iter().map(|symbol| to_entry(root, symbol)).collect())
+        .unwrap_or_default()
+}
+
+fn to_entry(root: &SyntaxNode, symbol: &SymbolTree) -> OutlineEntry {
+    OutlineEntry {
+        name: symbol.name.to_string(),
+        target: target_for_range(root, symbol.full_range),
+        children: symbol.children.iter().map(|child| to_entry(root, child)).collect(),
+    }
+}
+
+// Bindings navigate to their value, everything else to the closest expression.
+fn target_for_range(root: &SyntaxNode, range: TextRange) -> Option<AstPath> {
+    if !root.text_range().contains_range(range) {
+        return None;
+    }
+    let covering = match root.covering_element(range) {
+        rowan::NodeOrToken::Node(node) => node,
+        rowan::NodeOrToken::Token(token) => token.parent()?,
+    };
+    let target = covering.ancestors().find_map(|node| {
+        if node.kind() == SyntaxKind::ATTR_PATH_VALUE {
+            syntax::ast::AttrpathValue::cast(node)
+                .and_then(|attr| attr.value())
+                .map(|value| value.syntax().clone())
+        } else if syntax::ast::Expr::can_cast(node.kind()) {
+            Some(node)
+        } else {
+            None
+        }
+    })?;
+    Some(path_from_root(&target))
+}
+
+#[component]
+pub fn OutlineSidebar() -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let analysis = use_context::<Signal<(AnalysisHost, FileId)>>();
+    let entries = use_memo(move || document_outline(&ast.read(), &analysis.read()));
+    let route = use_route::<Route>();
+    let current = match route {
+        Route::NodeUI { path } => Some(path),
+        _ => None,
+    };
+    let items = entries.read().clone().into_iter().map(|entry| rsx! {
+        OutlineItem { entry: entry, current: current.clone() }
+    });
+    rsx! {
+        aside {
+            class: "outline-sidebar",
+            h4 { "Outline" }
+            ul {
+                class: "outline-tree",
+                { items }
+            }
+        }
+    }
+}
+
+#[component]
+fn OutlineItem(entry: OutlineEntry, current: Option<AstPath>) -> Element {
+    let contains_current = current.as_ref().is_some_and(|path| entry.contains(path));
+    let mut expanded = use_signal(|| contains_current);
+    use_effect(use_reactive!(|contains_current| {
+        if contains_current {
+            expanded.set(true);
+        }
+    }));
+    let is_current = current.is_some() && entry.target == current;
+    let has_children = !entry.children.is_empty();
+    let name = entry.name.clone();
+    let label = match entry.target.clone() {
+        Some(path) => rsx! {
+            Link {
+                class: if is_current { "outline-link current" } else { "outline-link" },
+                to: Route::NodeUI { path: path },
+                "{name}"
+            }
+        },
+        None => rsx! { span { class: "outline-link", "{name}" } },
+    };
+    let children = entry.children.into_iter().map(|child| rsx! {
+        OutlineItem { entry: child, current: current.clone() }
+    });
+    rsx! {
+        li {
+            class: "outline-item",
+            if has_children {
+                button {
+                    class: "outline-toggle",
+                    onclick: move |_| expanded.set(!expanded()),
+                    if expanded() { "▾" } else { "▸" }
+                }
+            } else {
+                span { class: "outline-toggle" }
+            }
+            { label }
+            if has_children && expanded() {
+                ul {
+                    class: "outline-tree",
+                    { children }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use syntax::SyntaxNode;
+use dioxus::prelude::*;
+use rfd::AsyncFileDialog;
+
+use crate::ast::attr_text;
+use crate::components::with::namespace_label;
+use crate::packages::{
+    cached_packages, load_listing, query_packages, search, PackageError, PackageIndex, QueriedPackages,
+};
+use crate::resolve::with_scope_for;
+
+const MAX_RESULTS: usize = 50;
+
+/// How the package `attr` is referred to at `at`: bare inside `with pkgs;`,
+/// `pkgs.<attr>` elsewhere.
+pub fn package_reference(at: &SyntaxNode, attr: &str) -> String {
+    let path = attr.split('.').map(attr_text).collect::<Vec<String>>().join(".");
+    let first = attr.split('.').next().unwrap_or(attr);
+    let in_with_pkgs = with_scope_for(at, first).is_some_and(|with| namespace_label(&with) == "pkgs");
+    if in_with_pkgs { path } else { format!("pkgs.{path}") }
+}
+
+#[component]
+pub fn PackagePicker(at: SyntaxNode, on_pick: EventHandler<String>) -> Element {
+    let mut index = use_context::<PackageIndex>().0;
+    let mut query = use_signal(String::new);
+    let mut status = use_signal(String::new);
+    use_hook(move || {
+        if index.peek().is_empty() {
+            match cached_packages() {
+                Ok(Some(packages)) => index.set(packages),
+                Ok(None) => {},
+                Err(e) => status.set(e.to_string()),
+            }
+        }
+    });
+    let mut show = move |result: Result<QueriedPackages, PackageError>| match result {
+        Ok(queried) => {
+            let count = format!("{} packages", queried.packages.len());
+            match queried.cache_error {
+                Some(e) => status.set(format!("{count}, {}", e.0)),
+                None => status.set(count),
+            }
+            index.set(queried.packages);
+        },
+        Err(e) => status.set(e.to_string()),
+    };
+    let refresh = move |_| async move {
+        status.set("Querying nix-env…".to_string());
+        show(query_packages().await);
+    };
+    let load = move |_| async move {
+        let Some(file) = AsyncFileDialog::new()
+            .add_filter("Package listings", &["json"])
+            .pick_file()
+            .await else {
+            return;
+        };
+        show(load_listing(file.path()));
+    };
+    let packages = index.read();
+    let hits = search(&packages, &query(), MAX_RESULTS).into_iter().map(|package| {
+        let reference = package_reference(&at, &package.attr);
+        let label = package.attr.clone();
+        let version = package.version.clone();
+        let description = package.description.clone();
+        rsx! {
+            li {
+                class: "package-hit",
+                onclick: move |_| on_pick.call(reference.clone()),
+                span { class: "package-attr", "{label}" }
+                span { class: "package-version", "{version}" }
+                span { class: "package-description", "{description}" }
+            }
+        }
+    });
+    rsx! {
+        div {
+            class: "package-picker",
+            div {
+                class: "package-picker-header",
+                input {
+                    class: "simple-input",
+                    placeholder: "Search packages",
+                    value: query(),
+                    oninput: move |e| query.set(e.value()),
+                }
+                button {
+                    title: "Query the packages of the local channels with nix-env",
+                    onclick: refresh,
+                    if packages.is_empty() { "Load packages" } else { "Refresh" }
+                }
+                button {
+                    title: "Read a listing saved from nix-env -qa --json --meta",
+                    onclick: load,
+                    "Load listing…"
+                }
+            }
+            if !status().is_empty() {
+                div { class: "package-status", "{status}" }
+            }
+            ul {
+                class: "package-hits",
+                { hits }
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+use rfd::AsyncFileDialog;
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::{AstNode, LiteralKind};
+use dioxus::prelude::*;
+
+use crate::{use_ast_node_strict};
+use crate::ast::{update_node_value};
+use crate::nix_path::{literal_for, resolve_literal, status, PathStatus};
+
+pub fn is_path(literal: &syntax::ast::Literal) -> bool {
+    matches!(literal.kind(), Some(LiteralKind::Path) | Some(LiteralKind::SearchPath))
+}
+
+fn is_path_literal(value: &str) -> bool {
+    let parsed = syntax::parse_file(value).syntax_node();
+    <syntax::ast::SourceFile as AstNode>::cast(parsed)
+        .and_then(|sf| sf.expr())
+        .is_some_and(|expr| match expr {
+            syntax::ast::Expr::Literal(literal) => {
+                is_path(&literal) && literal.syntax().text() == value
+            },
+            _ => false,
+        })
+}
+
+#[component]
+pub fn PathInput(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
+    let node = use_ast_node_strict!(ptr => syntax::ast::Literal);
+    let file_path = use_context::<Signal<PathBuf>>();
+    let value = node.read().syntax().text().to_string();
+    let is_search_path = matches!(node.read().kind(), Some(LiteralKind::SearchPath));
+    let mut draft = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<String>);
+    let (status_class, status_title) = match status(&value, &file_path.read()) {
+        PathStatus::Exists => ("path-status exists", "Target exists".to_string()),
+        PathStatus::Missing => ("path-status missing", "Target does not exist".to_string()),
+        PathStatus::Unresolved => ("path-status unresolved", "Not found in NIX_PATH".to_string()),
+    };
+    let resolved = resolve_literal(&value, &file_path.read())
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    let write = move |value: &str| {
+        update_node_value(
+            node.read().syntax().clone(),
+            value,
+            |syntax| {
+                <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                    .and_then(|sf| sf.expr())
+                    .map(|expr| expr.syntax().clone())
+            }
+        );
+    };
+    let prefer_home = value.starts_with("~/");
+    let pick = move |folder: bool| async move {
+        let start = resolve_literal(&node.peek().syntax().text().to_string(), &file_path.peek())
+            .and_then(|path| path.parent().map(|p| p.to_path_buf()));
+        let mut dialog = AsyncFileDialog::new();
+        if let Some(start) = start.filter(|dir| dir.is_dir()) {
+            dialog = dialog.set_directory(start);
+        }
+        let picked = if folder { dialog.pick_folder().await } else { dialog.pick_file().await };
+        let Some(picked) = picked else {
+            return;
+        };
+        match literal_for(&file_path.peek(), picked.path(), prefer_home) {
+            Ok(literal) => {
+                error.set(None);
+                draft.set(None);
+                write(&literal);
+            },
+            Err(e) => error.set(Some(e)),
+        }
+    };
+    let shown = draft().unwrap_or(value.clone());
+    rsx! {
+        span {
+            class: "path-input-wrapper",
+            span {
+                class: status_class,
+                title: status_title,
+            }
+            input {
+                class: if draft.read().is_some() { "path-input simple-input invalid" } else { "path-input simple-input" },
+                title: resolved,
+                value: shown,
+                oninput: move |e| {
+                    let value = e.value();
+                    if is_path_literal(value.trim()) {
+                        draft.set(None);
+                        write(value.trim());
+                    } else {
+                        draft.set(Some(value));
+                    }
+                }
+            }
+            if !is_search_path {
+                button {
+                    class: "path-pick",
+                    onclick: move |_| pick(false),
+                    "File…"
+                }
+                button {
+                    class: "path-pick",
+                    onclick: move |_| pick(true),
+                    "Folder…"
+                }
+            }
+            if let Some(error) = error() {
+                span { class: "path-error", "{error}" }
+            }
+        }
+    }
+}
@@ -5,6 +5,9 @@ use dioxus::prelude::*;
 
 use crate::{use_ast_node_strict};
 use crate::ast::{update_node_value};
+use crate::components::with::namespace_label;
+use crate::resolve::{rec_bound_names, with_scope};
+use declaro_core::scope::get_bindings_in_scope;
 
 #[component]
 pub fn RefInput(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
@@ -12,27 +15,46 @@ pub fn RefInput(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
     let selected = node.read().token().unwrap();
     let analysis = use_context::<Signal<(AnalysisHost, FileId)>>();
 
-    let bindings_option = get_bindings_in_scope(node.read().syntax(), &analysis.read());
+    let bindings_option = get_bindings_in_scope(node.read().syntax(), &analysis.read()).ok();
 
     if bindings_option.is_none() {
         return rsx! {
         }
     }
 
-    let bindings = bindings_option.unwrap();
+    let mut bindings = bindings_option.unwrap();
+    let rec_names = rec_bound_names(node.read().syntax());
+    for name in &rec_names {
+        if !bindings.contains(name) {
+            bindings.push(name.clone());
+        }
+    }
+    let from_with = with_scope(node.read().syntax()).map(|with| namespace_label(&with));
 
     let options = bindings
         .iter()
         .map(|label| {
             rsx! {
                 option {
+                    value: label.clone(),
                     selected: label == selected.text(),
-                    { label.clone() }
+                    if rec_names.contains(label) {
+                        "{label} (rec)"
+                    } else {
+                        "{label}"
+                    }
                 }
             }
         });
 
     rsx! {
+        if let Some(namespace) = from_with.as_ref() {
+            span {
+                class: "with-marker",
+                title: "Provided by `with {namespace};`",
+                "with {namespace}"
+            }
+        }
         select { 
             class: "ref-input simple-inout",
             onchange: move |e| {
@@ -51,19 +73,3 @@ pub fn RefInput(ptr: ReadSignal<SyntaxNodePtr>) -> Element {
         }
     }
 }
-
-pub fn get_bindings_in_scope(node: &SyntaxNode, analysis: &(AnalysisHost, FileId)) -> Option<Vec<String>>{
-    let snapshot = analysis.0.snapshot();
-    let scopes = snapshot.scopes(analysis.1).ok()?;
-    println!("expr_id: {:?}", SyntaxNodePtr::new(node));
-    let expr_id = snapshot
-        .source_map(analysis.1).unwrap()
-        .expr_for_node(SyntaxNodePtr::new(node))?;
-    let scope_id = scopes.scope_for_expr(expr_id)?;
-    Some(scopes
-        .ancestors(scope_id)
-        .filter_map(|scope| scope.as_definitions())
-        .flatten()
-        .map(|(name, _def)| name.to_string())
-        .collect::<Vec<String>>())
-}
@@ -0,0 +1,217 @@
+use std::sync::Arc;
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use crate::components::attribute_set::AttributeSetUI;
+use crate::components::string_input::StringInput;
+use crate::components::ref_input::RefInput;
+use crate::components::lambda::LambdaUI;
+use crate::components::number_input::{is_number_node, NumberInput};
+use crate::components::bool_input::{BoolInput, NullMarker};
+use crate::components::path_input::{is_path, PathInput};
+use crate::components::apply::ApplyUI;
+use crate::components::operator::{BinaryOpUI, UnaryOpUI};
+use crate::components::conditional::{AssertUI, IfElseUI};
+use crate::components::with::WithUI;
+use crate::components::select::SelectUI;
+use crate::components::list::ListUI;
+
+/// A graphical editor for some kind of expression. ExpressionUI renders a node
+/// with the highest priority editor that matches it, and offers the templates
+/// of all editors in its "change type" menu.
+pub trait Editor: Send + Sync {
+    fn matches(&self, node: &SyntaxNode) -> bool;
+
+    /// Editors for specific patterns, like `lib.mkOption { ... }`, should use a
+    /// priority above 0 to win over the built-in editor for the node kind.
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// Composed editors contain other ExpressionUIs and turn into links to a
+    /// subpage when nested too deep.
+    fn composed(&self) -> bool {
+        false
+    }
+
+    fn render(&self, ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element;
+
+    /// Menu labels and the source text that replaces a node when picked.
+    fn templates(&self) -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
+}
+
+struct BuiltinEditor {
+    matches: fn(&SyntaxNode) -> bool,
+    render: fn(ReadSignal<SyntaxNodePtr>, u16) -> Element,
+    composed: bool,
+    templates: &'static [(&'static str, &'static str)],
+}
+
+impl Editor for BuiltinEditor {
+    fn matches(&self, node: &SyntaxNode) -> bool {
+        (self.matches)(node)
+    }
+
+    fn composed(&self) -> bool {
+        self.composed
+    }
+
+    fn render(&self, ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+        (self.render)(ptr, nesting_level)
+    }
+
+    fn templates(&self) -> Vec<(&'static str, &'static str)> {
+        self.templates.to_vec()
+    }
+}
+
+// `true`, `false` and `null` are plain references to builtins in the syntax tree.
+fn builtin_constant(node: &SyntaxNode) -> Option<String> {
+    let name = syntax::ast::Ref::cast(node.clone())?.token()?.text().to_string();
+    matches!(name.as_str(), "true" | "false" | "null").then_some(name)
+}
+
+fn is_literal(node: &SyntaxNode, check: fn(&syntax::ast::Literal) -> bool) -> bool {
+    syntax::ast::Literal::cast(node.clone()).is_some_and(|lit| check(&lit))
+}
+
+const BUILTIN_EDITORS: [BuiltinEditor; 16] = [
+    BuiltinEditor {
+        matches: |node| syntax::ast::AttrSet::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { AttributeSetUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("Attribute Set", "{}")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::List::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { ListUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("List", "[ ]")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::Lambda::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { LambdaUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("Lambda", "{}:{}")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::Apply::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { ApplyUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("Function Call", "import ./.")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::BinaryOp::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { BinaryOpUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("Operation", "0 + 0")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::UnaryOp::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { UnaryOpUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: false,
+        templates: &[("Negation", "!false")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::IfThenElse::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { IfElseUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("If-Then-Else", "if true then null else null")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::Assert::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { AssertUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("Assertion", "assert true; null")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::With::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { WithUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: true,
+        templates: &[("With", "with pkgs; [ ]")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::String::can_cast(node.kind()),
+        render: |ptr, _| rsx! { StringInput { ptr: ptr } },
+        composed: false,
+        templates: &[("String", "\"\"")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::Ref::can_cast(node.kind()) && builtin_constant(node).is_none(),
+        render: |ptr, _| rsx! { RefInput { ptr: ptr } },
+        composed: false,
+        templates: &[("Reference", "ref")],
+    },
+    BuiltinEditor {
+        matches: |node| syntax::ast::Select::can_cast(node.kind()),
+        render: |ptr, nesting_level| rsx! { SelectUI { ptr: ptr, nesting_level: nesting_level } },
+        composed: false,
+        templates: &[("Selection", "ref.name")],
+    },
+    BuiltinEditor {
+        matches: is_number_node,
+        render: |ptr, _| rsx! { NumberInput { ptr: ptr } },
+        composed: false,
+        templates: &[("Integer", "0"), ("Float", "0.0")],
+    },
+    BuiltinEditor {
+        matches: |node| matches!(builtin_constant(node).as_deref(), Some("true" | "false")),
+        render: |ptr, _| rsx! { BoolInput { ptr: ptr } },
+        composed: false,
+        templates: &[("Boolean", "false")],
+    },
+    BuiltinEditor {
+        matches: |node| builtin_constant(node).as_deref() == Some("null"),
+        render: |_, _| rsx! { NullMarker {} },
+        composed: false,
+        templates: &[("Null", "null")],
+    },
+    BuiltinEditor {
+        matches: |node| is_literal(node, is_path),
+        render: |ptr, _| rsx! { PathInput { ptr: ptr } },
+        composed: false,
+        templates: &[("Path", "./.")],
+    },
+];
+
+/// The editors ExpressionUI chooses from, passed to `launch` and provided as
+/// context by the app.
+#[derive(Clone)]
+pub struct EditorRegistry {
+    editors: Vec<Arc<dyn Editor>>,
+}
+
+impl EditorRegistry {
+    pub fn empty() -> Self {
+        EditorRegistry { editors: Vec::new() }
+    }
+
+    pub fn builtin() -> Self {
+        let mut registry = Self::empty();
+        for editor in BUILTIN_EDITORS {
+            registry.register(editor);
+        }
+        registry
+    }
+
+    /// Adds an editor. Editors registered later win ties in priority, so
+    /// downstream crates can override built-in editors.
+    pub fn register(&mut self, editor: impl Editor + 'static) {
+        self.editors.push(Arc::new(editor));
+    }
+
+    pub fn editor_for(&self, node: &SyntaxNode) -> Option<Arc<dyn Editor>> {
+        self.editors.iter()
+            .enumerate()
+            .filter(|(_, editor)| editor.matches(node))
+            .max_by_key(|(i, editor)| (editor.priority(), *i))
+            .map(|(_, editor)| editor.clone())
+    }
+
+    pub fn templates(&self) -> Vec<(&'static str, &'static str)> {
+        self.editors.iter().flat_map(|editor| editor.templates()).collect()
+    }
+}
@@ -0,0 +1,60 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use dioxus::prelude::*;
+
+use crate::cursor::{move_cursor, page_for_node, Cursor, CursorSource};
+use crate::router::Route;
+use crate::search::{search, SearchHit};
+
+#[derive(Clone, Copy)]
+pub struct SearchVisible(pub Signal<bool>);
+
+#[component]
+pub fn SearchPanel() -> Element {
+    let ast = use_context::<Signal<SyntaxNode>>();
+    let cursor = use_context::<Signal<Option<Cursor>>>();
+    let mut query = use_signal(String::new);
+    let hits = use_memo(move || search(&ast.read(), &query.read()));
+    let nav = navigator();
+
+    let jump = move |hit: &SearchHit| {
+        nav.push(Route::NodeUI { path: page_for_node(&hit.node) });
+        move_cursor(cursor, SyntaxNodePtr::new(&hit.node), CursorSource::Search);
+    };
+
+    let results = hits.read().clone().into_iter().map(|hit| {
+        let kind = hit.kind.label();
+        let breadcrumb = hit.context.join(" › ");
+        let text = hit.text.clone();
+        rsx! {
+            li {
+                class: "search-hit",
+                onclick: move |_| jump(&hit),
+                span { class: "search-hit-kind", "{kind}" }
+                span { class: "search-hit-context", "{breadcrumb}" }
+                span { class: "search-hit-text", "{text}" }
+            }
+        }
+    });
+
+    rsx! {
+        div {
+            class: "search-panel",
+            input {
+                class: "search-input simple-input",
+                placeholder: "Search attributes, strings and references, e.g. services.nginx",
+                value: query(),
+                oninput: move |e| query.set(e.value()),
+            }
+            if !query.read().trim().is_empty() {
+                if hits.read().is_empty() {
+                    div { class: "search-empty", "No matches" }
+                } else {
+                    ul {
+                        class: "search-results",
+                        { results }
+                    }
+                }
+            }
+        }
+    }
+}
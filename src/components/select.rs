@@ -0,0 +1,173 @@
+use ide::{AnalysisHost, FileId, FilePos};
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+use closure::closure;
+
+use crate::components::ExpressionUI;
+use crate::{use_ast_node_strict};
+use crate::ast::{attr_key, attr_text, update_node_value};
+use crate::cursor::dom_id;
+
+// Attribute names nil's analysis knows for the segment `attr`, e.g. the keys
+// of a set bound by `let`.
+fn segment_completions(attr: &syntax::ast::Attr, analysis: &(AnalysisHost, FileId)) -> Vec<String> {
+    let pos = attr.syntax().text_range().end();
+    let mut labels = analysis.0.snapshot()
+        .completions(FilePos::new(analysis.1, pos), None)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| item.label.to_string())
+        .collect::<Vec<String>>();
+    labels.sort();
+    labels.dedup();
+    labels
+}
+
+fn update_select(select: &SyntaxNode, base: &str, segments: &[String], default: Option<&str>) {
+    let default = default.map(|d| format!(" or {d}")).unwrap_or_default();
+    update_node_value(
+        select.clone(),
+        &format!("{base}.{}{default}", segments.join(".")),
+        |syntax| {
+            <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                .and_then(|sf| sf.expr())
+                .map(|expr| expr.syntax().clone())
+        }
+    );
+}
+
+#[component]
+pub fn SelectUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let select = use_ast_node_strict!(ptr => syntax::ast::Select);
+    let analysis = use_context::<Signal<(AnalysisHost, FileId)>>();
+    let node = select.read().syntax().clone();
+    let Some(base) = select.read().set().map(|e| e.syntax().clone()) else {
+        return rsx! {};
+    };
+    let attrs = select.read().attrpath()
+        .map(|ap| ap.attrs().collect::<Vec<_>>())
+        .unwrap_or_default();
+    let default = select.read().default_expr().map(|e| e.syntax().clone());
+    let base_text = base.text().to_string();
+    let default_text = default.as_ref().map(|d| d.text().to_string());
+    let segments = attrs.iter()
+        .map(|attr| attr.syntax().text().to_string())
+        .collect::<Vec<String>>();
+    let base_ptr = SyntaxNodePtr::new(&base);
+    let list_prefix = dom_id(&ptr());
+
+    let segment_elements = attrs.iter().enumerate().map(|(i, attr)| {
+        let remove = closure!(clone node, clone base_text, clone segments, clone default_text, |_| {
+            let mut remaining = segments.clone();
+            remaining.remove(i);
+            update_select(&node, &base_text, &remaining, default_text.as_deref());
+        });
+        let body = match attr {
+            syntax::ast::Attr::Dynamic(dynamic) => {
+                let inner = dynamic.syntax().children().next().map(|e| SyntaxNodePtr::new(&e));
+                rsx! {
+                    span { class: "select-dynamic", "${{" }
+                    if let Some(inner_ptr) = inner {
+                        ExpressionUI { ptr: inner_ptr, nesting_level: nesting_level }
+                    }
+                    span { class: "select-dynamic", "}}" }
+                }
+            },
+            _ => {
+                let completions = segment_completions(attr, &analysis.read());
+                let list_id = format!("{list_prefix}-{i}");
+                let change = closure!(clone node, clone base_text, clone segments, clone default_text, |e: Event<FormData>| {
+                    let name = e.value();
+                    if name.is_empty() {
+                        return;
+                    }
+                    let mut edited = segments.clone();
+                    edited[i] = attr_text(&name);
+                    update_select(&node, &base_text, &edited, default_text.as_deref());
+                });
+                rsx! {
+                    input {
+                        class: "select-segment simple-input",
+                        list: "{list_id}",
+                        value: attr_key(attr),
+                        oninput: change,
+                    }
+                    datalist {
+                        id: "{list_id}",
+                        for key in completions {
+                            option { value: "{key}" }
+                        }
+                    }
+                }
+            },
+        };
+        rsx! {
+            span { class: "select-dot", "." }
+            span {
+                class: "select-segment-item",
+                { body }
+                if segments.len() > 1 {
+                    button {
+                        class: "attrpath-action",
+                        title: "Remove segment",
+                        onclick: remove,
+                        "✕"
+                    }
+                }
+            }
+        }
+    });
+
+    let add_segment = closure!(clone node, clone base_text, clone segments, clone default_text, |_| {
+        let mut extended = segments.clone();
+        extended.push("name".to_string());
+        update_select(&node, &base_text, &extended, default_text.as_deref());
+    });
+    let add_dynamic = closure!(clone node, clone base_text, clone segments, clone default_text, |_| {
+        let mut extended = segments.clone();
+        extended.push("${\"name\"}".to_string());
+        update_select(&node, &base_text, &extended, default_text.as_deref());
+    });
+    let toggle_default = closure!(clone node, clone base_text, clone segments, clone default_text, |_| {
+        let default = if default_text.is_some() { None } else { Some("null") };
+        update_select(&node, &base_text, &segments, default);
+    });
+
+    rsx! {
+        div {
+            class: "select-node",
+            div {
+                class: "select-path",
+                ExpressionUI { ptr: base_ptr, nesting_level: nesting_level }
+                { segment_elements }
+                button {
+                    class: "attrpath-action",
+                    title: "Add segment",
+                    onclick: add_segment,
+                    "+"
+                }
+                button {
+                    class: "attrpath-action",
+                    title: "Add dynamic segment",
+                    onclick: add_dynamic,
+                    "${{}}"
+                }
+            }
+            div {
+                class: "select-default",
+                if let Some(default_node) = default.as_ref() {
+                    span { class: "select-or", "or" }
+                    ExpressionUI { ptr: SyntaxNodePtr::new(default_node), nesting_level: nesting_level }
+                }
+                button {
+                    class: "select-toggle-default",
+                    onclick: toggle_default,
+                    if default_text.is_some() { "Remove default" } else { "Add `or` default" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use syntax::{SyntaxKind, SyntaxNode, SyntaxNodePtr, SyntaxToken};
+use dioxus::prelude::*;
+
+use crate::cursor::{expr_at_offset, move_cursor, page_for_node, Cursor, CursorSource};
+use crate::router::Route;
+
+#[derive(Clone, Copy)]
+pub struct SourcePaneVisible(pub Signal<bool>);
+
+const KEYWORDS: [&str; 10] = [
+    "assert", "else", "if", "in", "inherit", "let", "or", "rec", "then", "with",
+];
+
+fn token_class(token: &SyntaxToken) -> &'static str {
+    if token.kind() == SyntaxKind::COMMENT {
+        return "token-comment";
+    }
+    match token.parent().map(|p| p.kind()) {
+        Some(SyntaxKind::STRING) | Some(SyntaxKind::INDENT_STRING) => "token-string",
+        Some(SyntaxKind::LITERAL) => "token-literal",
+        Some(SyntaxKind::NAME) => "token-name",
+        Some(SyntaxKind::REF) => "token-ref",
+        _ if KEYWORDS.contains(&token.text()) => "token-keyword",
+        _ => "token-plain",
+    }
+}
+
+async fn follow_caret(
+    ast: Signal<SyntaxNode>,
+    cursor: Signal<Option<Cursor>>,
+    current: Route,
+) {
+    let offset = document::eval(
+        r#"return document.getElementById("source-input").selectionStart;"#
+    ).join::<usize>().await;
+    let Ok(offset) = offset else {
+        return;
+    };
+    let Some(node) = expr_at_offset(&ast.peek(), offset) else {
+        return;
+    };
+    let target = Route::NodeUI { path: page_for_node(&node) };
+    if target != current {
+        navigator().push(target);
+    }
+    move_cursor(cursor, SyntaxNodePtr::new(&node), CursorSource::Text);
+}
+
+#[component]
+pub fn SourcePane() -> Element {
+    let mut ast = use_context::<Signal<SyntaxNode>>();
+    let cursor = use_context::<Signal<Option<Cursor>>>();
+    let route = use_route::<Route>();
+    let text = ast.read().to_string();
+    let rows = text.lines().count() + 1;
+    let highlighted = cursor.read().as_ref().map(|c| c.ptr.text_range());
+
+    let spans = ast.read().descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .map(|token| {
+            let mut class = token_class(&token).to_owned();
+            if highlighted.is_some_and(|range| range.contains_range(token.text_range())) {
+                class.push_str(" cursor-range");
+            }
+            let text = token.text().to_string();
+            rsx! {
+                span { class: class, "{text}" }
+            }
+        })
+        .collect::<Vec<Element>>();
+
+    let click_route = route.clone();
+    rsx! {
+        div {
+            class: "source-pane",
+            div {
+                class: "source-editor",
+                pre {
+                    class: "source-highlight",
+                    { spans.into_iter() }
+                }
+                textarea {
+                    id: "source-input",
+                    class: "source-input",
+                    spellcheck: "false",
+                    wrap: "off",
+                    rows: "{rows}",
+                    value: text,
+                    oninput: move |e| {
+                        ast.set(syntax::parse_file(&e.value()).syntax_node());
+                    },
+                    onclick: move |_| follow_caret(ast, cursor, click_route.clone()),
+                    onkeyup: move |_| follow_caret(ast, cursor, route.clone()),
+                }
+            }
+        }
+    }
+}
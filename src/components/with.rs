@@ -0,0 +1,65 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use crate::components::ExpressionUI;
+use crate::{use_ast_node_strict};
+use crate::ast::{update_node_value};
+use crate::resolve::explicit_selections;
+
+#[component]
+pub fn WithUI(ptr: ReadSignal<SyntaxNodePtr>, nesting_level: u16) -> Element {
+    let with = use_ast_node_strict!(ptr => syntax::ast::With);
+    let node = with.read().syntax().clone();
+    let namespace = node.children().next().map(|n| SyntaxNodePtr::new(&n));
+    let body = node.children().nth(1).map(|n| SyntaxNodePtr::new(&n));
+    let replacement = explicit_selections(&node);
+    let can_replace = replacement.is_some();
+
+    rsx! {
+        div {
+            class: "with-node",
+            div {
+                class: "with-namespace",
+                span { class: "with-label", "with" }
+                if let Some(namespace_ptr) = namespace {
+                    ExpressionUI { ptr: namespace_ptr, nesting_level: nesting_level }
+                }
+            }
+            div {
+                class: "with-body",
+                if let Some(body_ptr) = body {
+                    ExpressionUI { ptr: body_ptr, nesting_level: nesting_level }
+                }
+            }
+            button {
+                class: "with-explicit",
+                disabled: !can_replace,
+                title: if can_replace { "Select every name from the namespace explicitly" } else { "The body inherits names from this with" },
+                onclick: move |_| {
+                    let Some(text) = replacement.as_ref() else {
+                        return;
+                    };
+                    update_node_value(
+                        node.clone(),
+                        text,
+                        |syntax| {
+                            <syntax::ast::SourceFile as AstNode>::cast(syntax.clone())
+                                .and_then(|sf| sf.expr())
+                                .map(|expr| expr.syntax().clone())
+                        }
+                    );
+                },
+                "Replace with explicit selections"
+            }
+        }
+    }
+}
+
+// Short label for the namespace a reference is taken from.
+pub fn namespace_label(with: &SyntaxNode) -> String {
+    with.children()
+        .next()
+        .map(|namespace| namespace.text().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,54 @@
+use syntax::{SyntaxNode, SyntaxNodePtr};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use crate::ast::{path_from_root, AstPath};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorSource {
+    Graphical,
+    Text,
+    Search,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cursor {
+    pub ptr: SyntaxNodePtr,
+    pub source: CursorSource,
+}
+
+pub fn move_cursor(mut cursor: Signal<Option<Cursor>>, ptr: SyntaxNodePtr, source: CursorSource) {
+    if cursor.peek().as_ref().map(|c| &c.ptr) != Some(&ptr) {
+        cursor.set(Some(Cursor { ptr, source }));
+    }
+}
+
+pub fn expr_at_offset(root: &SyntaxNode, offset: usize) -> Option<SyntaxNode> {
+    let offset = rowan::TextSize::try_from(offset).ok()?;
+    if offset > root.text_range().end() {
+        return None;
+    }
+    let token = root.token_at_offset(offset).right_biased()?;
+    token.parent_ancestors()
+        .find(|node| syntax::ast::Expr::can_cast(node.kind()))
+}
+
+// The page that shows `node`: the closest composed ancestor, so that the node
+// itself is rendered inline instead of behind a link.
+pub fn page_for_node(node: &SyntaxNode) -> AstPath {
+    node.ancestors()
+        .skip(1)
+        .find(|n| {
+            matches!(
+                syntax::ast::Expr::cast(n.clone()),
+                Some(syntax::ast::Expr::AttrSet(_)) | Some(syntax::ast::Expr::Lambda(_))
+            )
+        })
+        .map(|n| path_from_root(&n))
+        .unwrap_or(AstPath { indices: vec![0] })
+}
+
+pub fn dom_id(ptr: &SyntaxNodePtr) -> String {
+    let range = ptr.text_range();
+    format!("expr-{}-{}", u32::from(range.start()), u32::from(range.end()))
+}
@@ -0,0 +1,377 @@
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, HasBindings};
+
+use crate::ast::{attr_key, attrpath_keys, inherit_source};
+use crate::resolve::is_rec;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffStatus {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+impl DiffStatus {
+    pub fn class(&self) -> &'static str {
+        match self {
+            DiffStatus::Added => "diff-added",
+            DiffStatus::Removed => "diff-removed",
+            DiffStatus::Changed => "diff-changed",
+            DiffStatus::Unchanged => "diff-unchanged",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BindingDiff {
+    /// Full attribute path from the root of the compared expressions.
+    pub key: Vec<String>,
+    pub status: DiffStatus,
+    /// The bound value, or the `inherit` node for inherited names.
+    pub old: Option<SyntaxNode>,
+    pub new: Option<SyntaxNode>,
+    pub children: Vec<BindingDiff>,
+}
+
+impl BindingDiff {
+    pub fn label(&self) -> String {
+        self.key.join(".")
+    }
+
+    pub fn dom_id(&self) -> String {
+        format!("diff-{}", self.label())
+    }
+
+    /// Leaf changes in document order, used to step through a diff.
+    pub fn changes(&self) -> Vec<&BindingDiff> {
+        if self.children.is_empty() {
+            if self.status == DiffStatus::Unchanged { vec![] } else { vec![self] }
+        } else {
+            self.children.iter().flat_map(|child| child.changes()).collect()
+        }
+    }
+}
+
+pub fn diff_roots(old: &SyntaxNode, new: &SyntaxNode) -> Vec<BindingDiff> {
+    let old_expr = syntax::ast::SourceFile::cast(old.clone()).and_then(|sf| sf.expr());
+    let new_expr = syntax::ast::SourceFile::cast(new.clone()).and_then(|sf| sf.expr());
+    match (old_expr, new_expr) {
+        (Some(old), Some(new)) => diff_exprs(old.syntax(), new.syntax(), &[]),
+        _ => Vec::new(),
+    }
+}
+
+// Only expressions with bindings are compared structurally, everything else is
+// compared as a whole by its parent binding.
+fn diff_exprs(old: &SyntaxNode, new: &SyntaxNode, prefix: &[String]) -> Vec<BindingDiff> {
+    use syntax::ast::Expr;
+    match (Expr::cast(old.clone()), Expr::cast(new.clone())) {
+        (Some(Expr::Lambda(old)), Some(Expr::Lambda(new))) => {
+            match (old.body(), new.body()) {
+                (Some(old), Some(new)) => diff_exprs(old.syntax(), new.syntax(), prefix),
+                _ => Vec::new(),
+            }
+        },
+        (Some(Expr::Paren(old)), Some(Expr::Paren(new))) => {
+            match (old.expr(), new.expr()) {
+                (Some(old), Some(new)) => diff_exprs(old.syntax(), new.syntax(), prefix),
+                _ => Vec::new(),
+            }
+        },
+        (Some(Expr::AttrSet(old)), Some(Expr::AttrSet(new))) => {
+            diff_entries(&keyed_bindings(&old), &keyed_bindings(&new), prefix)
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// A name bound in a set. Names that are only defined through longer
+/// attribute paths (`a.b = x;`) or in several bindings have no single value,
+/// only the names below them.
+struct Entry {
+    name: String,
+    value: Option<SyntaxNode>,
+    children: Vec<Entry>,
+}
+
+fn keyed_bindings(set: &impl HasBindings) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for binding in set.bindings() {
+        match binding {
+            syntax::ast::Binding::AttrpathValue(attr) => {
+                let (Some(attrpath), Some(value)) = (attr.attrpath(), attr.value()) else {
+                    continue;
+                };
+                insert_entry(&mut entries, &attrpath_keys(&attrpath), value.syntax());
+            },
+            syntax::ast::Binding::Inherit(inherit) => {
+                for attr in inherit.attrs() {
+                    entries.push(Entry {
+                        name: attr_key(&attr),
+                        value: Some(inherit.syntax().clone()),
+                        children: Vec::new(),
+                    });
+                }
+            },
+        }
+    }
+    entries
+}
+
+fn nested_entries(value: &SyntaxNode) -> Vec<Entry> {
+    match syntax::ast::AttrSet::cast(value.clone()) {
+        Some(set) if !is_rec(set.syntax()) => keyed_bindings(&set),
+        _ => Vec::new(),
+    }
+}
+
+fn insert_entry(entries: &mut Vec<Entry>, keys: &[String], value: &SyntaxNode) {
+    let Some((first, rest)) = keys.split_first() else {
+        return;
+    };
+    let entry = match entries.iter().position(|entry| &entry.name == first) {
+        Some(idx) => {
+            // A second definition of the same name merges into a group.
+            let entry = &mut entries[idx];
+            entry.value = None;
+            entry
+        },
+        None => {
+            entries.push(Entry { name: first.clone(), value: None, children: Vec::new() });
+            let entry = entries.last_mut().unwrap();
+            if rest.is_empty() {
+                entry.value = Some(value.clone());
+            }
+            entry
+        },
+    };
+    if rest.is_empty() {
+        entry.children.extend(nested_entries(value));
+    } else {
+        insert_entry(&mut entry.children, rest, value);
+    }
+}
+
+fn diff_entries(old: &[Entry], new: &[Entry], prefix: &[String]) -> Vec<BindingDiff> {
+    let key_for = |name: &str| {
+        let mut key = prefix.to_vec();
+        key.push(name.to_string());
+        key
+    };
+    let mut diffs: Vec<BindingDiff> = new.iter()
+        .map(|new_entry| {
+            let key = key_for(&new_entry.name);
+            match old.iter().find(|old_entry| old_entry.name == new_entry.name) {
+                Some(old_entry) => diff_entry(old_entry, new_entry, key),
+                None => one_sided(new_entry, key, DiffStatus::Added),
+            }
+        })
+        .collect();
+    diffs.extend(old.iter()
+        .filter(|old_entry| !new.iter().any(|new_entry| new_entry.name == old_entry.name))
+        .map(|old_entry| one_sided(old_entry, key_for(&old_entry.name), DiffStatus::Removed)));
+    diffs
+}
+
+fn diff_entry(old: &Entry, new: &Entry, key: Vec<String>) -> BindingDiff {
+    if let (Some(old_value), Some(new_value)) = (&old.value, &new.value) {
+        return diff_values(old_value, new_value, key);
+    }
+    // At least one side spreads the name over several bindings, so only the
+    // names below it can be compared.
+    let children = diff_entries(&old.children, &new.children, &key);
+    let status = if children.iter().all(|child| child.status == DiffStatus::Unchanged) {
+        DiffStatus::Unchanged
+    } else {
+        DiffStatus::Changed
+    };
+    BindingDiff {
+        key,
+        status,
+        old: old.value.clone(),
+        new: new.value.clone(),
+        children,
+    }
+}
+
+fn diff_values(old: &SyntaxNode, new: &SyntaxNode, key: Vec<String>) -> BindingDiff {
+    let children = diff_exprs(old, new, &key);
+    let same = if old.kind() == SyntaxKind::INHERIT && new.kind() == SyntaxKind::INHERIT {
+        // Siblings inherited by the same statement don't change this name.
+        match (inherit_source(old), inherit_source(new)) {
+            (Some(old), Some(new)) => same_tokens(&old, &new),
+            (None, None) => true,
+            _ => false,
+        }
+    } else {
+        same_tokens(old, new)
+    };
+    let status = if same {
+        DiffStatus::Unchanged
+    } else {
+        DiffStatus::Changed
+    };
+    BindingDiff {
+        key,
+        status,
+        old: Some(old.clone()),
+        new: Some(new.clone()),
+        children,
+    }
+}
+
+fn one_sided(entry: &Entry, key: Vec<String>, status: DiffStatus) -> BindingDiff {
+    let children = match entry.value {
+        Some(_) => Vec::new(),
+        None => entry.children.iter()
+            .map(|child| {
+                let mut child_key = key.clone();
+                child_key.push(child.name.clone());
+                one_sided(child, child_key, status)
+            })
+            .collect(),
+    };
+    let (old, new) = match status {
+        DiffStatus::Removed => (entry.value.clone(), None),
+        _ => (None, entry.value.clone()),
+    };
+    BindingDiff { key, status, old, new, children }
+}
+
+/// The key prefix that `diff_roots` uses for the bindings of `set`, if the set
+/// is reachable from the root through bindings, lambda bodies and parentheses.
+pub fn set_key(set: &SyntaxNode) -> Option<Vec<String>> {
+    let mut key = Vec::new();
+    let mut current = set.clone();
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            SyntaxKind::SOURCE_FILE => {
+                key.reverse();
+                return Some(key);
+            },
+            SyntaxKind::LAMBDA | SyntaxKind::PAREN | SyntaxKind::ATTR_SET => {},
+            SyntaxKind::ATTR_PATH_VALUE => {
+                let attr = syntax::ast::AttrpathValue::cast(parent.clone())?;
+                key.extend(attrpath_keys(&attr.attrpath()?).into_iter().rev());
+            },
+            _ => return None,
+        }
+        current = parent;
+    }
+    None
+}
+
+pub fn find_diff<'a>(diffs: &'a [BindingDiff], key: &[String]) -> Option<&'a BindingDiff> {
+    diffs.iter().find_map(|diff| {
+        if diff.key == key {
+            Some(diff)
+        } else if key.starts_with(&diff.key) {
+            find_diff(&diff.children, key)
+        } else {
+            None
+        }
+    })
+}
+
+/// Bindings directly below `prefix` that only exist in the old document.
+pub fn removed_below<'a>(diffs: &'a [BindingDiff], prefix: &[String]) -> Vec<&'a BindingDiff> {
+    let siblings = if prefix.is_empty() {
+        diffs
+    } else {
+        match find_diff(diffs, prefix) {
+            Some(parent) => &parent.children,
+            None => return Vec::new(),
+        }
+    };
+    // Names that were only defined through longer attribute paths are shown
+    // by the bindings that defined them.
+    siblings.iter()
+        .filter(|diff| diff.status == DiffStatus::Removed)
+        .flat_map(|diff| diff.changes())
+        .collect()
+}
+
+/// Compares two nodes while ignoring whitespace and comments.
+pub fn same_tokens(a: &SyntaxNode, b: &SyntaxNode) -> bool {
+    let tokens = |node: &SyntaxNode| {
+        node.descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| !token.kind().is_trivia())
+            .map(|token| token.text().to_string())
+            .collect::<Vec<String>>()
+    };
+    tokens(a) == tokens(b)
+}
+
+/// The document the open file is compared against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffBase {
+    pub label: String,
+    pub root: SyntaxNode,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> Vec<BindingDiff> {
+        diff_roots(&syntax::parse_file(old).syntax_node(), &syntax::parse_file(new).syntax_node())
+    }
+
+    fn key(path: &[&str]) -> Vec<String> {
+        path.iter().map(|segment| segment.to_string()).collect()
+    }
+
+    fn status(diffs: &[BindingDiff], path: &[&str]) -> Option<DiffStatus> {
+        find_diff(diffs, &key(path)).map(|diff| diff.status)
+    }
+
+    #[test]
+    fn matches_attrpaths_with_nested_sets() {
+        let diffs = diff("{ a.b = 1; }", "{ a = { b = 1; }; }");
+        assert_eq!(status(&diffs, &["a"]), Some(DiffStatus::Unchanged));
+        assert!(diffs.iter().all(|diff| diff.changes().is_empty()));
+
+        let diffs = diff("{ a.b = 1; a.c = 2; }", "{ a = { b = 1; c = 3; }; }");
+        assert_eq!(status(&diffs, &["a"]), Some(DiffStatus::Changed));
+        assert_eq!(status(&diffs, &["a", "b"]), Some(DiffStatus::Unchanged));
+        assert_eq!(status(&diffs, &["a", "c"]), Some(DiffStatus::Changed));
+    }
+
+    #[test]
+    fn reports_bindings_below_removed_groups() {
+        let diffs = diff("{ a.b = 1; a.c = 2; d = 3; }", "{ d = 3; }");
+        let removed = removed_below(&diffs, &[]).into_iter().map(BindingDiff::label).collect::<Vec<_>>();
+        assert_eq!(removed, ["a.b", "a.c"]);
+    }
+
+    #[test]
+    fn compares_inherited_names() {
+        let diffs = diff("{ inherit a; b = 1; }", "{ b = 1; inherit (pkgs) c; }");
+        let added = find_diff(&diffs, &key(&["c"])).unwrap();
+        assert_eq!(added.status, DiffStatus::Added);
+        assert_eq!(added.new.as_ref().map(SyntaxNode::kind), Some(SyntaxKind::INHERIT));
+        assert_eq!(status(&diffs, &["a"]), Some(DiffStatus::Removed));
+        assert_eq!(status(&diffs, &["b"]), Some(DiffStatus::Unchanged));
+
+        let diffs = diff("{ inherit a; }", "{ inherit a b; }");
+        assert_eq!(status(&diffs, &["a"]), Some(DiffStatus::Unchanged));
+        assert_eq!(status(&diffs, &["b"]), Some(DiffStatus::Added));
+    }
+
+    #[test]
+    fn keeps_dots_in_quoted_keys() {
+        let diffs = diff("{ \"a.b\" = 1; }", "{ a.b = 1; }");
+        assert_eq!(status(&diffs, &["a.b"]), Some(DiffStatus::Removed));
+        assert_eq!(status(&diffs, &["a"]), Some(DiffStatus::Added));
+        assert_eq!(status(&diffs, &["a", "b"]), Some(DiffStatus::Added));
+    }
+
+    #[test]
+    fn keys_nested_sets_by_their_attrpath() {
+        let root = syntax::parse_file("{ a.\"b.c\" = { d = 1; }; }").syntax_node();
+        let inner = root.descendants().filter(|node| node.kind() == SyntaxKind::ATTR_SET).nth(1).unwrap();
+        assert_eq!(set_key(&inner), Some(key(&["a", "b.c"])));
+    }
+}
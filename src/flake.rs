@@ -0,0 +1,468 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use dioxus::prelude::*;
+use serde::Deserialize;
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, HasBindings};
+
+use crate::ast::{attr_text, attrpath_keys};
+use crate::attrpath::{logical_tree, LogicalNode};
+use crate::resolve::is_rec;
+
+/// Whether the flake view is shown instead of the node editor.
+#[derive(Clone, Copy)]
+pub struct FlakeMode(pub Signal<bool>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlakeError(pub String);
+
+impl std::fmt::Display for FlakeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "flake: {}", self.0)
+    }
+}
+
+/// The top-level attribute set of a flake.nix: a set that defines `outputs`.
+pub fn flake_set(root: &SyntaxNode) -> Option<syntax::ast::AttrSet> {
+    let set = syntax::ast::SourceFile::cast(root.clone())?
+        .expr()
+        .and_then(|expr| syntax::ast::AttrSet::cast(expr.syntax().clone()))?;
+    logical_tree(&set).iter().any(|node| node.name == "outputs").then_some(set)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlakeInput {
+    pub name: String,
+    pub url: Option<SyntaxNode>,
+    /// Whether the input is written as just its url, `inputs.foo = "github:...";`.
+    pub shorthand: bool,
+    pub flake: Option<SyntaxNode>,
+    /// `inputs.<name>.follows`, making the input an alias of another one.
+    pub follows: Option<SyntaxNode>,
+    /// `inputs.<name>.inputs.<input>.follows` for inputs of the input.
+    pub input_follows: Vec<(String, SyntaxNode)>,
+}
+
+fn child<'a>(node: &'a LogicalNode, name: &str) -> Option<&'a LogicalNode> {
+    node.children.iter().find(|child| child.name == name)
+}
+
+fn leaf(node: &LogicalNode, name: &str) -> Option<SyntaxNode> {
+    child(node, name).and_then(|child| child.value.clone())
+}
+
+fn flake_input(node: &LogicalNode) -> FlakeInput {
+    let input_follows = child(node, "inputs")
+        .map(|inputs| inputs.children.iter()
+            .filter_map(|input| Some((input.name.clone(), leaf(input, "follows")?)))
+            .collect())
+        .unwrap_or_default();
+    FlakeInput {
+        name: node.name.clone(),
+        // `inputs.foo = "github:..."` is a shorthand for the url.
+        url: leaf(node, "url").or_else(|| node.value.clone()),
+        shorthand: node.value.is_some(),
+        flake: leaf(node, "flake"),
+        follows: leaf(node, "follows"),
+        input_follows,
+    }
+}
+
+pub fn flake_inputs(set: &syntax::ast::AttrSet) -> Vec<FlakeInput> {
+    logical_tree(set).iter()
+        .find(|node| node.name == "inputs")
+        .map(|inputs| inputs.children.iter().map(flake_input).collect())
+        .unwrap_or_default()
+}
+
+/// The lambda bound to `outputs`.
+pub fn outputs_lambda(set: &syntax::ast::AttrSet) -> Option<SyntaxNode> {
+    logical_tree(set).into_iter()
+        .find(|node| node.name == "outputs")?
+        .value
+        .filter(|value| value.kind() == SyntaxKind::LAMBDA)
+}
+
+/// The set new input settings are added to and the key prefix they need
+/// there: the `inputs = { };` set if there is one, else the flake itself.
+pub fn inputs_target(set: &syntax::ast::AttrSet) -> (SyntaxNode, String) {
+    let inputs_set = set.bindings().find_map(|binding| match binding {
+        syntax::ast::Binding::AttrpathValue(attr) if attr.attrpath().is_some_and(|ap| attrpath_keys(&ap) == ["inputs"]) => {
+            attr.value()
+                .and_then(|value| syntax::ast::AttrSet::cast(value.syntax().clone()))
+                .filter(|inputs| !is_rec(inputs.syntax()))
+        },
+        _ => None,
+    });
+    match inputs_set {
+        Some(inputs) => (inputs.syntax().clone(), String::new()),
+        None => (set.syntax().clone(), "inputs.".to_string()),
+    }
+}
+
+/// The binding that sets `setting` (e.g. `url` or `flake`) of the input `name`.
+pub fn input_setting(set: &syntax::ast::AttrSet, name: &str, setting: &str) -> (SyntaxNode, String) {
+    let (target, prefix) = inputs_target(set);
+    (target, format!("{prefix}{}.{setting}", attr_text(name)))
+}
+
+/// The value that replaces the url of a shorthand input to mark it as not a
+/// flake; adding `inputs.foo.flake` next to `inputs.foo = url;` would define
+/// the input twice.
+pub fn non_flake_value(url: &SyntaxNode) -> String {
+    format!("{{ url = {}; flake = false; }}", url.text())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawInput {
+    Node(String),
+    Follows(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct RawLocked {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    rev: Option<String>,
+    #[serde(rename = "lastModified", default)]
+    last_modified: Option<i64>,
+    #[serde(default)]
+    owner: Option<String>,
+    #[serde(default)]
+    repo: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawNode {
+    #[serde(default)]
+    inputs: BTreeMap<String, RawInput>,
+    #[serde(default)]
+    locked: Option<RawLocked>,
+}
+
+#[derive(Deserialize)]
+struct RawLock {
+    nodes: BTreeMap<String, RawNode>,
+    root: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LockInput {
+    Node(String),
+    /// An input path from the root, e.g. `["nixpkgs"]`.
+    Follows(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockNode {
+    pub key: String,
+    pub source: String,
+    pub rev: Option<String>,
+    pub last_modified: Option<i64>,
+    pub inputs: Vec<(String, LockInput)>,
+}
+
+/// The parsed flake.lock next to a flake.nix.
+///
+/// This doesn't go through nix-interop: its flake.lock support resolves the
+/// inputs of the root to store paths with the `nix` command, while the flake
+/// view needs every node with its `follows`, revision and date, and has to
+/// work without nix being installed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlakeLock {
+    pub root: String,
+    pub nodes: BTreeMap<String, LockNode>,
+}
+
+fn source(locked: &RawLocked) -> String {
+    match (locked.owner.as_ref(), locked.repo.as_ref()) {
+        (Some(owner), Some(repo)) => format!("{}:{owner}/{repo}", locked.kind),
+        _ => locked.url.clone()
+            .or_else(|| locked.path.clone())
+            .unwrap_or_else(|| locked.kind.clone()),
+    }
+}
+
+impl FlakeLock {
+    pub fn parse(json: &str) -> Result<FlakeLock, FlakeError> {
+        let raw: RawLock = serde_json::from_str(json).map_err(|e| FlakeError(e.to_string()))?;
+        let nodes = raw.nodes.into_iter()
+            .map(|(key, node)| {
+                let inputs = node.inputs.into_iter()
+                    .map(|(name, input)| (name, match input {
+                        RawInput::Node(key) => LockInput::Node(key),
+                        RawInput::Follows(path) => LockInput::Follows(path),
+                    }))
+                    .collect();
+                let lock_node = LockNode {
+                    key: key.clone(),
+                    source: node.locked.as_ref().map(source).unwrap_or_default(),
+                    rev: node.locked.as_ref().and_then(|locked| locked.rev.clone()),
+                    last_modified: node.locked.as_ref().and_then(|locked| locked.last_modified),
+                    inputs,
+                };
+                (key, lock_node)
+            })
+            .collect();
+        Ok(FlakeLock { root: raw.root, nodes })
+    }
+
+    pub fn root_node(&self) -> Option<&LockNode> {
+        self.nodes.get(&self.root)
+    }
+
+    /// The node an input path from the root resolves to, following `follows`.
+    /// Follows that lead back to themselves resolve to nothing.
+    pub fn resolve(&self, path: &[String]) -> Option<&LockNode> {
+        self.resolve_following(path, &mut Vec::new())
+    }
+
+    // `following` holds the follows targets being resolved, so a malformed
+    // lock with cyclic follows can't recurse forever.
+    fn resolve_following(&self, path: &[String], following: &mut Vec<Vec<String>>) -> Option<&LockNode> {
+        let mut node = self.root_node()?;
+        for name in path {
+            let (_, input) = node.inputs.iter().find(|(input, _)| input == name)?;
+            node = match input {
+                LockInput::Node(key) => self.nodes.get(key)?,
+                LockInput::Follows(target) => {
+                    if following.contains(target) {
+                        return None;
+                    }
+                    following.push(target.clone());
+                    let resolved = self.resolve_following(target, following);
+                    following.pop();
+                    resolved?
+                },
+            };
+        }
+        Some(node)
+    }
+
+    /// Groups of node keys that lock the same source more than once.
+    pub fn duplicates(&self) -> Vec<Vec<String>> {
+        let mut by_source: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for node in self.nodes.values().filter(|node| !node.source.is_empty()) {
+            by_source.entry(node.source.as_str()).or_default().push(node.key.clone());
+        }
+        by_source.into_values().filter(|keys| keys.len() > 1).collect()
+    }
+
+    /// The keys of every node in `duplicates`.
+    pub fn duplicate_keys(&self) -> BTreeSet<String> {
+        self.duplicates().into_iter().flatten().collect()
+    }
+
+    pub fn follows_suggestions(&self) -> Vec<FollowsSuggestion> {
+        let Some(root) = self.root_node() else {
+            return Vec::new();
+        };
+        // The flake's own inputs by the source they lock.
+        let own = root.inputs.iter()
+            .filter_map(|(name, _)| Some((name.clone(), self.resolve(std::slice::from_ref(name))?)))
+            .collect::<Vec<(String, &LockNode)>>();
+        let mut suggestions = Vec::new();
+        for (input, node) in &own {
+            for (nested, nested_input) in &node.inputs {
+                let LockInput::Node(key) = nested_input else {
+                    continue;
+                };
+                let Some(nested_node) = self.nodes.get(key).filter(|n| !n.source.is_empty()) else {
+                    continue;
+                };
+                let candidates = own.iter()
+                    .filter(|(_, target)| target.source == nested_node.source && target.key != nested_node.key)
+                    .map(|(name, _)| name)
+                    .collect::<Vec<&String>>();
+                // Prefer the input with the same name, e.g. nixpkgs for nixpkgs.
+                let target = candidates.iter().find(|name| **name == nested).or(candidates.first());
+                if let Some(target) = target {
+                    suggestions.push(FollowsSuggestion {
+                        input: input.clone(),
+                        nested: nested.clone(),
+                        target: (*target).clone(),
+                    });
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+pub fn load_lock(flake_file: &Path) -> Result<FlakeLock, FlakeError> {
+    let lock_file = flake_file.with_file_name("flake.lock");
+    let contents = fs::read_to_string(&lock_file)
+        .map_err(|e| FlakeError(format!("{}: {e}", lock_file.display())))?;
+    FlakeLock::parse(&contents)
+}
+
+/// A unix timestamp as `YYYY-MM-DD`.
+pub fn format_date(timestamp: i64) -> String {
+    // Days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = timestamp.div_euclid(86400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+pub fn short_rev(rev: &str) -> &str {
+    &rev[..rev.len().min(7)]
+}
+
+/// A `follows` that would let an input of an input reuse one of the flake's
+/// own inputs instead of locking a second copy of the same source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FollowsSuggestion {
+    pub input: String,
+    pub nested: String,
+    pub target: String,
+}
+
+impl FollowsSuggestion {
+    /// The setting passed to `input_setting` for `input`.
+    pub fn setting(&self) -> String {
+        format!("inputs.{}.follows", attr_text(&self.nested))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"{
+        "nodes": {
+            "root": {
+                "inputs": {
+                    "agenix": "agenix",
+                    "home-manager": "home-manager",
+                    "nixpkgs": "nixpkgs",
+                    "sops-nix": "sops-nix",
+                    "unstable": ["nixpkgs"]
+                }
+            },
+            "agenix": {
+                "inputs": { "nixpkgs": "nixpkgs_3" },
+                "locked": { "type": "github", "owner": "ryantm", "repo": "agenix", "rev": "a1" }
+            },
+            "home-manager": {
+                "inputs": { "nixpkgs": "nixpkgs_2" },
+                "locked": { "type": "github", "owner": "nix-community", "repo": "home-manager", "rev": "b2" }
+            },
+            "nixpkgs": {
+                "locked": {
+                    "type": "github",
+                    "owner": "NixOS",
+                    "repo": "nixpkgs",
+                    "rev": "0123456789abcdef",
+                    "lastModified": 1700000000
+                },
+                "original": { "type": "github", "owner": "NixOS", "repo": "nixpkgs" }
+            },
+            "nixpkgs_2": {
+                "locked": { "type": "github", "owner": "NixOS", "repo": "nixpkgs", "rev": "fedcba9876543210" }
+            },
+            "sops-nix": {
+                "inputs": { "nixpkgs": ["nixpkgs"] },
+                "locked": { "type": "path", "path": "/home/me/sops-nix" }
+            }
+        },
+        "root": "root",
+        "version": 7
+    }"#;
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn key(node: Option<&LockNode>) -> Option<&str> {
+        node.map(|node| node.key.as_str())
+    }
+
+    #[test]
+    fn parses_nodes() {
+        let lock = FlakeLock::parse(LOCK).unwrap();
+        assert_eq!(lock.root, "root");
+        let nixpkgs = &lock.nodes["nixpkgs"];
+        assert_eq!(nixpkgs.source, "github:NixOS/nixpkgs");
+        assert_eq!(nixpkgs.rev.as_deref().map(short_rev), Some("0123456"));
+        assert_eq!(nixpkgs.last_modified, Some(1700000000));
+        assert_eq!(lock.nodes["sops-nix"].source, "/home/me/sops-nix");
+        assert_eq!(lock.nodes["sops-nix"].inputs, [("nixpkgs".to_string(), LockInput::Follows(path(&["nixpkgs"])))]);
+        assert!(FlakeLock::parse("{ \"nodes\": {} }").is_err());
+    }
+
+    #[test]
+    fn resolves_input_paths_through_follows() {
+        let lock = FlakeLock::parse(LOCK).unwrap();
+        assert_eq!(key(lock.resolve(&[])), Some("root"));
+        assert_eq!(key(lock.resolve(&path(&["unstable"]))), Some("nixpkgs"));
+        assert_eq!(key(lock.resolve(&path(&["home-manager", "nixpkgs"]))), Some("nixpkgs_2"));
+        assert_eq!(key(lock.resolve(&path(&["sops-nix", "nixpkgs"]))), Some("nixpkgs"));
+        // agenix's nixpkgs is missing from the nodes.
+        assert_eq!(key(lock.resolve(&path(&["agenix", "nixpkgs"]))), None);
+        assert_eq!(key(lock.resolve(&path(&["flake-utils"]))), None);
+    }
+
+    #[test]
+    fn gives_up_on_cyclic_follows() {
+        let lock = FlakeLock::parse(r#"{
+            "nodes": { "root": { "inputs": { "a": ["b"], "b": ["a"], "c": ["c", "d"] } } },
+            "root": "root"
+        }"#).unwrap();
+        assert_eq!(key(lock.resolve(&path(&["a"]))), None);
+        assert_eq!(key(lock.resolve(&path(&["c"]))), None);
+    }
+
+    #[test]
+    fn groups_nodes_locking_the_same_source() {
+        let lock = FlakeLock::parse(LOCK).unwrap();
+        assert_eq!(lock.duplicates(), [path(&["nixpkgs", "nixpkgs_2"])]);
+        assert_eq!(lock.duplicate_keys(), BTreeSet::from(["nixpkgs".to_string(), "nixpkgs_2".to_string()]));
+    }
+
+    #[test]
+    fn suggests_follows_for_duplicated_inputs() {
+        let lock = FlakeLock::parse(LOCK).unwrap();
+        // sops-nix already follows, agenix's nixpkgs node is missing.
+        let suggestions = lock.follows_suggestions();
+        assert_eq!(suggestions, [FollowsSuggestion {
+            input: "home-manager".to_string(),
+            nested: "nixpkgs".to_string(),
+            target: "nixpkgs".to_string(),
+        }]);
+        assert_eq!(suggestions[0].setting(), "inputs.nixpkgs.follows");
+
+        let followed = LOCK.replace(r#""inputs": { "nixpkgs": "nixpkgs_2" }"#, r#""inputs": { "nixpkgs": ["nixpkgs"] }"#);
+        assert!(FlakeLock::parse(&followed).unwrap().follows_suggestions().is_empty());
+    }
+
+    #[test]
+    fn suggests_any_input_locking_the_source_when_names_differ() {
+        let renamed = LOCK.replace(r#""inputs": { "nixpkgs": "nixpkgs_2" }"#, r#""inputs": { "pkgs": "nixpkgs_2" }"#);
+        let suggestions = FlakeLock::parse(&renamed).unwrap().follows_suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!((suggestions[0].nested.as_str(), suggestions[0].target.as_str()), ("pkgs", "nixpkgs"));
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_date(-86_400), "1969-12-31");
+    }
+}
@@ -0,0 +1,224 @@
+use std::path::Path;
+use std::process::Command;
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::AstNode;
+use dioxus::prelude::*;
+
+use declaro_core::EditError;
+
+use crate::ast::{
+    apply_edit, attr_key, attr_text, attrpath_keys, inherit_source, inherit_text, line_indent, source_expr,
+    with_binding, with_node_value, with_replaced_bindings, without_node,
+};
+use crate::diff::{set_key, BindingDiff, DiffStatus};
+
+#[derive(Clone, Copy)]
+pub struct GitMode(pub Signal<bool>);
+
+/// The committed version of the open file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GitHead {
+    pub root: SyntaxNode,
+}
+
+/// Diff between the committed and the edited document.
+#[derive(Clone, Copy)]
+pub struct HeadDiff(pub Memo<Vec<BindingDiff>>);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GitError(pub String);
+
+impl std::fmt::Display for GitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "git: {}", self.0)
+    }
+}
+
+fn git(file: &Path, args: &[&str]) -> Result<String, GitError> {
+    let dir = file.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| GitError(e.to_string()))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(GitError(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+fn file_name(file: &Path) -> Result<String, GitError> {
+    file.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| GitError(format!("{} is not a file", file.display())))
+}
+
+pub fn head_contents(file: &Path) -> Result<String, GitError> {
+    git(file, &["show", &format!("HEAD:./{}", file_name(file)?)])
+}
+
+pub fn load_head(file: &Path) -> Result<GitHead, GitError> {
+    let contents = head_contents(file)?;
+    Ok(GitHead { root: syntax::parse_file(&contents).syntax_node() })
+}
+
+pub fn commit_file(file: &Path, message: &str) -> Result<String, GitError> {
+    let name = file_name(file)?;
+    git(file, &["add", "--", &name])?;
+    git(file, &["commit", "-m", message, "--", &name])
+}
+
+// The binding whose value is `value`.
+fn binding_of(value: &SyntaxNode) -> Result<SyntaxNode, EditError> {
+    value.parent()
+        .filter(|binding| binding.kind() == SyntaxKind::ATTR_PATH_VALUE)
+        .ok_or(EditError::NotApplicable("the value is not bound by a binding"))
+}
+
+// The HEAD definition `old` written as a binding for `attrpath`, relative to
+// the set it is put into.
+fn old_definition(old: &SyntaxNode, attrpath: &[String]) -> Result<String, EditError> {
+    if old.kind() != SyntaxKind::INHERIT {
+        let attrpath = attrpath.iter().map(|key| attr_text(key)).collect::<Vec<String>>().join(".");
+        return Ok(format!("{attrpath} = {};", old.text()));
+    }
+    match attrpath {
+        [name] => {
+            let source = inherit_source(old).map(|paren| paren.text().to_string());
+            Ok(inherit_text(source.as_deref(), std::slice::from_ref(name)))
+        },
+        _ => Err(EditError::NotApplicable("an inherited name can't be restored below a nested path")),
+    }
+}
+
+// The new root after replacing the current definition of `name`, either its
+// binding or its entry in an `inherit`, with `replacement`.
+fn replace_definition(new: &SyntaxNode, name: &str, replacement: Option<String>) -> Result<SyntaxNode, EditError> {
+    if new.kind() != SyntaxKind::INHERIT {
+        let binding = binding_of(new)?;
+        return match replacement {
+            Some(text) => with_replaced_bindings(&binding, &text),
+            None => without_node(&binding),
+        };
+    }
+    let names = syntax::ast::Inherit::cast(new.clone())
+        .map(|inherit| inherit.attrs().map(|attr| attr_key(&attr)).filter(|other| other != name).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let source = inherit_source(new).map(|paren| paren.text().to_string());
+    let remaining = (!names.is_empty()).then(|| inherit_text(source.as_deref(), &names));
+    let bindings = remaining.into_iter().chain(replacement).collect::<Vec<String>>();
+    if bindings.is_empty() {
+        without_node(new)
+    } else {
+        with_replaced_bindings(new, &bindings.join(&format!("\n{}", line_indent(new))))
+    }
+}
+
+/// Whether the change has a single definition on each side it touches, so
+/// `reverted` can undo it. Names spread over several bindings have none.
+pub fn can_revert(diff: &BindingDiff) -> bool {
+    match diff.status {
+        DiffStatus::Added => diff.new.is_some(),
+        DiffStatus::Changed => diff.old.is_some() && diff.new.is_some(),
+        DiffStatus::Removed => diff.old.is_some(),
+        DiffStatus::Unchanged => false,
+    }
+}
+
+/// The new root after undoing `diff`. Removed bindings are rebuilt in `set`
+/// from their key, so they fit however the path is split today.
+pub fn reverted(diff: &BindingDiff, set: &SyntaxNode) -> Result<SyntaxNode, EditError> {
+    let nothing = || EditError::NotApplicable("nothing to revert");
+    let name = diff.key.last().ok_or_else(nothing)?;
+    match (diff.status, &diff.old, &diff.new) {
+        (DiffStatus::Added, _, Some(new)) => replace_definition(new, name, None),
+        (DiffStatus::Changed, Some(old), Some(new))
+            if old.kind() != SyntaxKind::INHERIT && new.kind() != SyntaxKind::INHERIT => {
+            with_node_value(new, &old.text().to_string(), source_expr)
+        },
+        (DiffStatus::Changed, Some(old), Some(new)) => {
+            let attrpath = match new.kind() {
+                SyntaxKind::INHERIT => vec![name.clone()],
+                _ => syntax::ast::AttrpathValue::cast(binding_of(new)?)
+                    .and_then(|binding| binding.attrpath())
+                    .map(|attrpath| attrpath_keys(&attrpath))
+                    .ok_or_else(nothing)?,
+            };
+            replace_definition(new, name, Some(old_definition(old, &attrpath)?))
+        },
+        (DiffStatus::Removed, Some(old), _) => {
+            let prefix = set_key(set).ok_or(EditError::NotApplicable("the set is not reachable through bindings"))?;
+            let attrpath = diff.key.strip_prefix(prefix.as_slice())
+                .filter(|attrpath| !attrpath.is_empty())
+                .ok_or(EditError::NotApplicable("the binding doesn't belong in this set"))?;
+            with_binding(set, &old_definition(old, attrpath)?)
+        },
+        _ => Err(nothing()),
+    }
+}
+
+pub fn revert_binding(diff: &BindingDiff, set: SyntaxNode) {
+    apply_edit(reverted(diff, &set));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::{diff_roots, find_diff};
+
+    fn parse(source: &str) -> SyntaxNode {
+        syntax::parse_file(source).syntax_node()
+    }
+
+    fn key(path: &[&str]) -> Vec<String> {
+        path.iter().map(|segment| segment.to_string()).collect()
+    }
+
+    fn sets(root: &SyntaxNode) -> Vec<SyntaxNode> {
+        root.descendants().filter(|node| node.kind() == SyntaxKind::ATTR_SET).collect()
+    }
+
+    #[test]
+    fn restores_removed_bindings_relative_to_the_set() {
+        let head = parse("{ a.b = 1; a.c = 2; }");
+        let current = parse("{ a = { c = 2; }; }");
+        let diffs = diff_roots(&head, &current);
+        let removed = find_diff(&diffs, &key(&["a", "b"])).unwrap();
+        let new_root = reverted(removed, &sets(&current)[1]).unwrap();
+        assert_eq!(new_root.to_string(), "{ a = { c = 2; b = 1; }; }");
+
+        // `a` is a group at HEAD, so there is no single value to go back to.
+        let group = find_diff(&diffs, &key(&["a"])).unwrap();
+        assert_eq!(group.status, DiffStatus::Changed);
+        assert!(!can_revert(group));
+        assert!(reverted(group, &sets(&current)[0]).is_err());
+    }
+
+    #[test]
+    fn reverts_values_in_place() {
+        let head = parse("{ a.b = 1; }");
+        let current = parse("{ a.b = 2; }");
+        let diffs = diff_roots(&head, &current);
+        let changed = find_diff(&diffs, &key(&["a", "b"])).unwrap();
+        assert!(can_revert(changed));
+        assert_eq!(reverted(changed, &sets(&current)[0]).unwrap().to_string(), "{ a.b = 1; }");
+    }
+
+    #[test]
+    fn reverts_inherited_names() {
+        let head = parse("{ inherit (pkgs) a; }");
+        let current = parse("{ inherit (pkgs) a b; }");
+        let diffs = diff_roots(&head, &current);
+        let added = find_diff(&diffs, &key(&["b"])).unwrap();
+        assert_eq!(reverted(added, &sets(&current)[0]).unwrap().to_string(), "{ inherit (pkgs) a; }");
+
+        let current = parse("{ }");
+        let diffs = diff_roots(&head, &current);
+        let removed = find_diff(&diffs, &key(&["a"])).unwrap();
+        assert_eq!(reverted(removed, &sets(&current)[0]).unwrap().to_string(), "{ inherit (pkgs) a; }");
+    }
+}
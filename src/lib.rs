@@ -0,0 +1,209 @@
+//! Declaro as a library, for builds that register their own editors before
+//! launching the app. See `examples/mk_option.rs`.
+
+use dioxus::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use rfd::AsyncFileDialog;
+
+mod ast;
+mod components;
+mod router;
+mod hooks;
+mod cursor;
+mod diff;
+mod git;
+mod search;
+mod nix_path;
+mod options;
+mod packages;
+mod flake;
+mod cli;
+use declaro_core::{attrpath, comments, operators, resolve};
+use declaro_core::scope::analysis_for;
+
+
+
+const FAVICON: Asset = asset!("/assets/favicon.ico");
+const MAIN_CSS: Asset = asset!("/assets/main.css");
+
+pub use components::expression::ExpressionUI;
+pub use components::registry::{Editor, EditorRegistry};
+
+/// Runs a command line subcommand if one was given, and otherwise opens the
+/// editor with the editors of `registry`.
+pub fn launch(registry: EditorRegistry) {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if cli::is_command(&args) {
+        std::process::exit(cli::run(&args));
+    }
+    dioxus::LaunchBuilder::new()
+        .with_context(registry)
+        .launch(App);
+}
+
+#[component]
+fn App() -> Element {
+    let mut file_path = use_signal(|| {PathBuf::from("./example.nix")});
+    let ast = hooks::use_derivation(move || {
+        let contents = fs::read_to_string(file_path.read().clone()).expect("Could not read file");
+        syntax::parse_file(&contents).syntax_node()
+    });
+    let mut analysis_host = use_signal(|| {
+        analysis_for(&ast.peek()).unwrap_or_else(|| ide::AnalysisHost::new_single_file(""))
+    });
+    // While the source is being typed it can be empty or hold no expression;
+    // the last analysis stays in place until it parses to one again.
+    use_effect(move || {
+        if let Some(analysis) = analysis_for(&ast.read()) {
+            analysis_host.set(analysis);
+        }
+    });
+    let mut source_visible = use_signal(|| false);
+    let mut outline_visible = use_signal(|| false);
+    let mut search_visible = use_signal(|| false);
+    let cursor = use_signal(|| None::<cursor::Cursor>);
+    let mut diff_base = use_signal(|| None::<diff::DiffBase>);
+    let mut git_mode = use_signal(|| false);
+    let git_head = use_signal(|| None::<git::GitHead>);
+    let mut loaded_options = use_signal(|| None::<options::OptionSet>);
+    let mut options_error = use_signal(|| None::<String>);
+    let mut edit_status = use_signal(|| None::<String>);
+    let options_root = use_signal(Vec::<String>::new);
+    let package_index = use_signal(Vec::<packages::Package>::new);
+    let mut flake_mode = use_signal(|| false);
+    let head_diff = use_memo(move || {
+        git_head.read().as_ref()
+            .map(|head| diff::diff_roots(&head.root, &ast.read()))
+            .unwrap_or_default()
+    });
+    use_context_provider(|| ast);
+    use_context_provider(|| analysis_host);
+    use_context_provider(|| cursor);
+    use_context_provider(|| diff_base);
+    use_context_provider(|| file_path);
+    use_context_provider(|| git_head);
+    use_context_provider(|| ast::EditStatus(edit_status));
+    use_context_provider(|| git::GitMode(git_mode));
+    use_context_provider(|| git::HeadDiff(head_diff));
+    use_context_provider(|| components::source_view::SourcePaneVisible(source_visible));
+    use_context_provider(|| components::outline::OutlineVisible(outline_visible));
+    use_context_provider(|| components::search::SearchVisible(search_visible));
+    use_context_provider(|| options::LoadedOptions(loaded_options));
+    use_context_provider(|| options::OptionsRoot(options_root));
+    use_context_provider(|| packages::PackageIndex(package_index));
+    use_context_provider(|| flake::FlakeMode(flake_mode));
+    rsx! {
+        document::Link { rel: "icon", href: FAVICON }
+        document::Stylesheet { href: MAIN_CSS } 
+        div {
+            class: "menu",
+            button {
+                id: "open-file",
+                id: "open-file",
+                onclick: move |_| async move {
+                    let file = AsyncFileDialog::new()
+                        .add_filter("Nix files", &["nix"])
+                        .pick_file()
+                        .await.unwrap();
+                    let path = file.clone().path().to_path_buf();
+
+                    file_path.set(path.clone());
+                },
+                "Open"
+            }
+            button {
+                onclick: move |_| {
+                    let serialized = crate::ast::serialize_document(&ast.read());
+                    fs::write(file_path.read().clone(), serialized).expect("Could not write to file");
+                },
+                id: "save-file",
+                "Save"
+            }
+            button {
+                id: "compare-file",
+                onclick: move |_| async move {
+                    let Some(file) = AsyncFileDialog::new()
+                        .add_filter("Nix files", &["nix"])
+                        .pick_file()
+                        .await else {
+                        return;
+                    };
+                    let contents = fs::read_to_string(file.path()).expect("Could not read file");
+                    diff_base.set(Some(diff::DiffBase {
+                        label: file.file_name(),
+                        root: syntax::parse_file(&contents).syntax_node(),
+                    }));
+                },
+                "Compare"
+            }
+            button {
+                id: "load-options",
+                title: "Load an options.json from the NixOS or home-manager manual",
+                onclick: move |_| async move {
+                    let Some(file) = AsyncFileDialog::new()
+                        .add_filter("Option declarations", &["json"])
+                        .pick_file()
+                        .await else {
+                        return;
+                    };
+                    match options::load_options(file.path()) {
+                        Ok(loaded) => {
+                            options_error.set(None);
+                            loaded_options.set(Some(loaded));
+                        },
+                        Err(e) => options_error.set(Some(e.to_string())),
+                    }
+                },
+                if loaded_options.read().is_some() { "Reload Options" } else { "Options" }
+            }
+            if let Some(error) = options_error() {
+                span {
+                    class: "menu-error",
+                    onclick: move |_| options_error.set(None),
+                    title: "Dismiss",
+                    "{error}"
+                }
+            }
+            if let Some(error) = edit_status() {
+                span {
+                    class: "menu-error",
+                    onclick: move |_| edit_status.set(None),
+                    title: "Dismiss",
+                    "{error}"
+                }
+            }
+            if flake::flake_set(&ast.read()).is_some() {
+                button {
+                    id: "toggle-flake",
+                    onclick: move |_| flake_mode.set(!flake_mode()),
+                    if flake_mode() { "Leave Flake View" } else { "Flake" }
+                }
+            }
+            button {
+                id: "toggle-git",
+                onclick: move |_| git_mode.set(!git_mode()),
+                if git_mode() { "Leave Git Mode" } else { "Git" }
+            }
+            button {
+                id: "toggle-search",
+                onclick: move |_| search_visible.set(!search_visible()),
+                if search_visible() { "Hide Search" } else { "Search" }
+            }
+            button {
+                id: "toggle-outline",
+                onclick: move |_| outline_visible.set(!outline_visible()),
+                if outline_visible() { "Hide Outline" } else { "Outline" }
+            }
+            button {
+                id: "toggle-source",
+                onclick: move |_| source_visible.set(!source_visible()),
+                if source_visible() { "Hide Source" } else { "Show Source" }
+            }
+        }
+        div {
+            class: if source_visible() || outline_visible() { "app-container wide" } else { "app-container" },
+            Router::<router::Route> {}
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathStatus {
+    Exists,
+    Missing,
+    /// Search paths whose prefix is not in `NIX_PATH`.
+    Unresolved,
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+fn search_path_entries() -> Vec<(Option<String>, PathBuf)> {
+    env::var("NIX_PATH")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((prefix, path)) => (Some(prefix.to_string()), PathBuf::from(path)),
+            None => (None, PathBuf::from(entry)),
+        })
+        .collect()
+}
+
+// Same lookup as `builtins.findFile`: the first `NIX_PATH` entry that has the
+// requested file wins.
+fn resolve_search_path(name: &str) -> Option<PathBuf> {
+    let (head, rest) = name.split_once('/').unwrap_or((name, ""));
+    search_path_entries().into_iter().find_map(|(prefix, dir)| {
+        let candidate = match prefix {
+            Some(prefix) if prefix == head => dir.join(rest),
+            Some(_) => return None,
+            None => dir.join(name),
+        };
+        candidate.exists().then_some(candidate)
+    })
+}
+
+/// Resolves the text of a path literal relative to the file it appears in.
+pub fn resolve_literal(literal: &str, file: &Path) -> Option<PathBuf> {
+    if let Some(name) = literal.strip_prefix('<').and_then(|l| l.strip_suffix('>')) {
+        return resolve_search_path(name);
+    }
+    if let Some(rest) = literal.strip_prefix("~/") {
+        return home_dir().map(|home| home.join(rest));
+    }
+    let path = Path::new(literal);
+    if path.is_absolute() {
+        return Some(path.to_path_buf());
+    }
+    let dir = file.parent().unwrap_or(Path::new("."));
+    Some(dir.join(path))
+}
+
+pub fn status(literal: &str, file: &Path) -> PathStatus {
+    match resolve_literal(literal, file) {
+        Some(path) if path.exists() => PathStatus::Exists,
+        Some(_) => PathStatus::Missing,
+        None => PathStatus::Unresolved,
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().unwrap_or_default().join(path)
+    };
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn relative_to(dir: &Path, target: &Path) -> PathBuf {
+    let dir = normalize(dir);
+    let target = normalize(target);
+    let common = dir.components()
+        .zip(target.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative = PathBuf::new();
+    for _ in dir.components().skip(common) {
+        relative.push("..");
+    }
+    for component in target.components().skip(common) {
+        relative.push(component);
+    }
+    relative
+}
+
+fn is_literal_safe(path: &str) -> bool {
+    path.chars().all(|c| c.is_ascii_alphanumeric() || "._-+/~".contains(c))
+}
+
+/// The path literal pointing from `file` to `target`. Keeps the `~/` form when
+/// `prefer_home` is set and the target is inside the home directory.
+pub fn literal_for(file: &Path, target: &Path, prefer_home: bool) -> Result<String, String> {
+    let home_relative = home_dir()
+        .filter(|_| prefer_home)
+        .and_then(|home| normalize(target).strip_prefix(normalize(&home)).ok().map(Path::to_path_buf));
+    let literal = match home_relative {
+        Some(rest) => format!("~/{}", rest.display()),
+        None => {
+            let dir = file.parent().unwrap_or(Path::new("."));
+            let relative = relative_to(dir, target);
+            let text = relative.display().to_string();
+            if text.is_empty() {
+                "./.".to_string()
+            } else if text.starts_with("..") {
+                text
+            } else {
+                format!("./{text}")
+            }
+        },
+    };
+    if is_literal_safe(&literal) {
+        Ok(literal)
+    } else {
+        Err(format!("{literal} contains characters that are not allowed in path literals"))
+    }
+}
@@ -0,0 +1,790 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use dioxus::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, HasStringParts};
+
+use declaro_core::document::parse_attrpath;
+
+use crate::ast::{attr_text, attrpath_keys, string_literal};
+use crate::diff::set_key;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionsError(pub String);
+
+impl std::fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "options: {}", self.0)
+    }
+}
+
+/// The type of an option, parsed from the description in options.json such as
+/// `null or (list of string)`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionType {
+    Bool,
+    Int { min: Option<i64>, max: Option<i64> },
+    Float,
+    Str,
+    Path,
+    Package,
+    Enum(Vec<String>),
+    ListOf(Box<OptionType>),
+    AttrsOf(Box<OptionType>),
+    NullOr(Box<OptionType>),
+    Either(Vec<OptionType>),
+    Submodule,
+    Other(String),
+}
+
+// Splits `text` at `separator` where it is outside of parentheses and quoted
+// enum values, so `(list of string) or string` has two parts.
+fn split_top_level<'a>(text: &'a str, separator: &str) -> Vec<&'a str> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if quoted => i += 1,
+            b'"' => quoted = !quoted,
+            b'(' if !quoted => depth += 1,
+            b')' if !quoted => depth = depth.saturating_sub(1),
+            _ if depth == 0 && !quoted && bytes[i..].starts_with(separator.as_bytes()) => {
+                parts.push(&text[start..i]);
+                i += separator.len();
+                start = i;
+                continue;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+// Removes parentheses around the whole text, but not those of
+// `(a) or (b)`.
+fn strip_parens(text: &str) -> &str {
+    let text = text.trim();
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 1 && i == text.len() - 1 => return text[1..i].trim(),
+            ')' if depth == 1 => return text,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => return text,
+            _ => {},
+        }
+    }
+    text
+}
+
+// `16 bit unsigned integer; between 0 and 65535 (both inclusive)`
+fn integer_range(text: &str) -> (Option<i64>, Option<i64>) {
+    if let Some(range) = text.split("between ").nth(1) {
+        let mut bounds = range.split(" and ");
+        let min = bounds.next().and_then(|b| b.trim().parse().ok());
+        let max = bounds.next()
+            .and_then(|b| b.split_whitespace().next())
+            .and_then(|b| b.parse().ok());
+        return (min, max);
+    }
+    if text.starts_with("unsigned") || text.contains("unsigned integer") {
+        return (Some(0), None);
+    }
+    if text.starts_with("positive") {
+        return (Some(1), None);
+    }
+    (None, None)
+}
+
+// `one of "a", "b", 1`
+fn enum_values(text: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let mut value = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next()),
+                        '"' => break,
+                        _ => value.push(c),
+                    }
+                }
+                values.push(value);
+            },
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut value = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '.') {
+                    value.push(c);
+                }
+                values.push(value);
+            },
+            _ => {},
+        }
+    }
+    values
+}
+
+impl OptionType {
+    pub fn parse(description: &str) -> OptionType {
+        let text = strip_parens(description);
+        if let Some(inner) = text.strip_prefix("null or ") {
+            return OptionType::NullOr(Box::new(OptionType::parse(inner)));
+        }
+        let alternatives = split_top_level(text, " or ");
+        if alternatives.len() > 1 {
+            return OptionType::Either(alternatives.into_iter().map(OptionType::parse).collect());
+        }
+        if let Some(inner) = text.strip_prefix("list of ") {
+            return OptionType::ListOf(Box::new(OptionType::parse(inner)));
+        }
+        if let Some(inner) = text.strip_prefix("attribute set of ")
+            .or_else(|| text.strip_prefix("lazy attribute set of "))
+        {
+            return OptionType::AttrsOf(Box::new(OptionType::parse(inner)));
+        }
+        if let Some(values) = text.strip_prefix("one of ") {
+            return OptionType::Enum(enum_values(values));
+        }
+        match text {
+            "boolean" => OptionType::Bool,
+            "path" | "absolute path" => OptionType::Path,
+            "package" => OptionType::Package,
+            "submodule" => OptionType::Submodule,
+            _ if text.contains("integer") || text == "signed integer" => {
+                let (min, max) = integer_range(text);
+                OptionType::Int { min, max }
+            },
+            _ if text.starts_with("floating point number") => OptionType::Float,
+            _ if text.contains("string") => OptionType::Str,
+            _ => OptionType::Other(text.to_string()),
+        }
+    }
+
+    /// A value of this type to start editing from, which also decides the
+    /// editor the new binding is shown with.
+    pub fn template(&self) -> String {
+        match self {
+            OptionType::Bool => "false".to_string(),
+            OptionType::Int { min, .. } => min.unwrap_or(0).to_string(),
+            OptionType::Float => "0.0".to_string(),
+            OptionType::Str => "\"\"".to_string(),
+            OptionType::Path => "./.".to_string(),
+            OptionType::Package => "pkgs.hello".to_string(),
+            OptionType::Enum(values) => values.first()
+                .map(|value| match value.parse::<f64>() {
+                    Ok(_) => value.clone(),
+                    Err(_) => string_literal(value),
+                })
+                .unwrap_or_else(|| "null".to_string()),
+            OptionType::ListOf(_) => "[ ]".to_string(),
+            OptionType::AttrsOf(_) | OptionType::Submodule => "{ }".to_string(),
+            OptionType::Either(alternatives) => alternatives.first()
+                .map(OptionType::template)
+                .unwrap_or_else(|| "null".to_string()),
+            OptionType::NullOr(_) | OptionType::Other(_) => "null".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionDecl {
+    pub name: String,
+    pub path: Vec<String>,
+    pub type_description: String,
+    pub option_type: OptionType,
+    pub description: String,
+    pub default: Option<String>,
+    pub example: Option<String>,
+    pub read_only: bool,
+}
+
+#[derive(Deserialize)]
+struct RawOption {
+    #[serde(rename = "type", default)]
+    type_description: String,
+    #[serde(default)]
+    description: Value,
+    #[serde(default)]
+    default: Option<Value>,
+    #[serde(default)]
+    example: Option<Value>,
+    #[serde(rename = "readOnly", default)]
+    read_only: bool,
+}
+
+/// Renders a JSON value from options.json as Nix source.
+fn json_to_nix(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => string_literal(s),
+        Value::Array(items) => {
+            let items = items.iter().map(|item| format!("{} ", json_to_nix(item))).collect::<String>();
+            format!("[ {items}]")
+        },
+        Value::Object(fields) => {
+            let fields = fields.iter()
+                .map(|(key, value)| format!("{} = {}; ", attr_text(key), json_to_nix(value)))
+                .collect::<String>();
+            format!("{{ {fields}}}")
+        },
+    }
+}
+
+// Defaults, examples and descriptions are either plain values or tagged as
+// `{ "_type": "literalExpression", "text": ... }`.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Object(fields) if fields.contains_key("_type") => fields.get("text")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        _ => json_to_nix(value),
+    }
+}
+
+fn display_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        _ => display_value(value),
+    }
+}
+
+fn is_placeholder(segment: &str) -> bool {
+    segment == "*" || (segment.starts_with('<') && segment.ends_with('>'))
+}
+
+// The option paths by segment, so lookups only follow the segments of a path
+// instead of comparing it with every declaration.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct OptionIndex {
+    option: Option<usize>,
+    children: BTreeMap<String, OptionIndex>,
+}
+
+impl OptionIndex {
+    fn insert(&mut self, path: &[String], option: usize) {
+        match path.split_first() {
+            Some((first, rest)) => self.children.entry(first.clone()).or_default().insert(rest, option),
+            None => self.option = Some(option),
+        }
+    }
+
+    // The nodes `path` leads to, exact names before placeholders like `<name>`
+    // that match any segment.
+    fn nodes<'a>(&'a self, path: &[String], found: &mut Vec<&'a OptionIndex>) {
+        let Some((first, rest)) = path.split_first() else {
+            found.push(self);
+            return;
+        };
+        if let Some(child) = self.children.get(first) {
+            child.nodes(rest, found);
+        }
+        for (segment, child) in &self.children {
+            if segment != first && is_placeholder(segment) {
+                child.nodes(rest, found);
+            }
+        }
+    }
+
+    fn descendants(&self, found: &mut Vec<usize>) {
+        for child in self.children.values() {
+            found.extend(child.option);
+            child.descendants(found);
+        }
+    }
+}
+
+/// The options of an options.json produced by the NixOS or home-manager manual
+/// build.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionSet {
+    options: Vec<OptionDecl>,
+    index: OptionIndex,
+}
+
+impl OptionSet {
+    pub fn new(options: Vec<OptionDecl>) -> OptionSet {
+        let mut index = OptionIndex::default();
+        for (i, option) in options.iter().enumerate() {
+            index.insert(&option.path, i);
+        }
+        OptionSet { options, index }
+    }
+
+    fn nodes(&self, path: &[String]) -> Vec<&OptionIndex> {
+        let mut found = Vec::new();
+        self.index.nodes(path, &mut found);
+        found
+    }
+
+    /// The declaration for the attribute path `path`. Placeholders like
+    /// `<name>` match any segment, but exact names win.
+    pub fn find(&self, path: &[String]) -> Option<&OptionDecl> {
+        self.nodes(path).into_iter()
+            .find_map(|node| node.option)
+            .map(|i| &self.options[i])
+    }
+
+    /// The declarations below `prefix`, e.g. the options that can be set in an
+    /// attribute set at `prefix`.
+    pub fn below<'a>(&'a self, prefix: &'a [String]) -> impl Iterator<Item = &'a OptionDecl> + 'a {
+        let mut found = Vec::new();
+        for node in self.nodes(prefix) {
+            node.descendants(&mut found);
+        }
+        found.sort();
+        found.dedup();
+        found.into_iter().map(|i| &self.options[i])
+    }
+
+    /// For an attribute path that is not an option, the index of the first
+    /// unknown segment and the closest known names for it. Paths below a
+    /// declared option are values of that option and never unknown.
+    pub fn unknown_segment(&self, path: &[String]) -> Option<(usize, Vec<String>)> {
+        for i in 0..path.len() {
+            if i > 0 && self.find(&path[..i]).is_some() {
+                return None;
+            }
+            // Every indexed path leads to an option.
+            if !self.nodes(&path[..=i]).is_empty() {
+                continue;
+            }
+            let mut candidates = self.nodes(&path[..i]).into_iter()
+                .flat_map(|node| node.children.keys())
+                .filter(|segment| !is_placeholder(segment))
+                .collect::<BTreeSet<&String>>()
+                .into_iter()
+                .map(|segment| (edit_distance(segment, &path[i]), segment.clone()))
+                .filter(|(distance, segment)| *distance <= (segment.len() / 3).max(2))
+                .collect::<Vec<(usize, String)>>();
+            candidates.sort();
+            return Some((i, candidates.into_iter().take(3).map(|(_, segment)| segment).collect()));
+        }
+        None
+    }
+
+    /// The names that can follow `prefix` in an option path.
+    pub fn next_segments(&self, prefix: &[String]) -> Vec<String> {
+        self.nodes(prefix).into_iter()
+            .flat_map(|node| node.children.keys())
+            .filter(|segment| !is_placeholder(segment))
+            .cloned()
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Completions for the option path `typed` below `prefix`: the segments
+    /// typed so far followed by each name that can come next. Completing one
+    /// segment at a time keeps the list short even for all NixOS options.
+    pub fn completions(&self, prefix: &[String], typed: &str) -> Vec<String> {
+        let typed = typed.trim();
+        let head = match typed.strip_suffix('.') {
+            Some(head) => parse_attrpath(head).ok(),
+            None if typed.is_empty() => Some(Vec::new()),
+            None => parse_attrpath(typed).ok().map(|mut keys| {
+                keys.pop();
+                keys
+            }),
+        };
+        let Some(head) = head else {
+            return Vec::new();
+        };
+        let written = head.iter().map(|key| format!("{}.", attr_text(key))).collect::<String>();
+        let mut path = prefix.to_vec();
+        path.extend(head);
+        self.next_segments(&path).into_iter()
+            .map(|segment| format!("{written}{}", attr_text(&segment)))
+            .collect()
+    }
+}
+
+// Option names quote segments that contain dots, like
+// `boot.kernel.sysctl."net.ipv4.tcp_keepalive_time"`.
+fn option_path(name: &str) -> Vec<String> {
+    parse_attrpath(name).unwrap_or_else(|_| name.split('.').map(str::to_string).collect())
+}
+
+pub fn load_options(file: &Path) -> Result<OptionSet, OptionsError> {
+    let contents = fs::read_to_string(file).map_err(|e| OptionsError(e.to_string()))?;
+    let raw: BTreeMap<String, RawOption> = serde_json::from_str(&contents)
+        .map_err(|e| OptionsError(format!("{} is not an options.json: {e}", file.display())))?;
+    let options = raw.into_iter()
+        .map(|(name, raw)| OptionDecl {
+            path: option_path(&name),
+            option_type: OptionType::parse(&raw.type_description),
+            type_description: raw.type_description,
+            description: display_text(&raw.description),
+            default: raw.default.as_ref().map(display_value),
+            example: raw.example.as_ref().map(display_value),
+            read_only: raw.read_only,
+            name,
+        })
+        .collect();
+    Ok(OptionSet::new(options))
+}
+
+/// The loaded options, if any.
+#[derive(Clone, Copy)]
+pub struct LoadedOptions(pub Signal<Option<OptionSet>>);
+
+/// The key of the attribute set that option paths are relative to, e.g. the
+/// body of a NixOS module.
+#[derive(Clone, Copy)]
+pub struct OptionsRoot(pub Signal<Vec<String>>);
+
+/// The option path an attribute set with the key `set_key` configures, if it
+/// is below the options root.
+pub fn option_prefix(root: &[String], set_key: &[String]) -> Option<Vec<String>> {
+    set_key.strip_prefix(root).map(<[String]>::to_vec)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The kind of value a literal expression has, or `None` when it can't be
+/// known without evaluation.
+fn value_kind(node: &SyntaxNode) -> Option<&'static str> {
+    match node.kind() {
+        SyntaxKind::PAREN => value_kind(&node.children().next()?),
+        SyntaxKind::STRING | SyntaxKind::INDENT_STRING => Some("string"),
+        SyntaxKind::LIST => Some("list"),
+        SyntaxKind::ATTR_SET => Some("attribute set"),
+        SyntaxKind::LAMBDA => Some("function"),
+        SyntaxKind::REF => match node.text().to_string().as_str() {
+            "true" | "false" => Some("boolean"),
+            "null" => Some("null"),
+            _ => None,
+        },
+        SyntaxKind::LITERAL => match syntax::ast::Literal::cast(node.clone())?.kind()? {
+            syntax::ast::LiteralKind::Int => Some("integer"),
+            syntax::ast::LiteralKind::Float => Some("float"),
+            syntax::ast::LiteralKind::Path | syntax::ast::LiteralKind::SearchPath => Some("path"),
+            syntax::ast::LiteralKind::Uri => Some("string"),
+        },
+        _ => None,
+    }
+}
+
+fn unwrap_parens(node: &SyntaxNode) -> SyntaxNode {
+    match node.kind() {
+        SyntaxKind::PAREN => node.children().next().map(|inner| unwrap_parens(&inner)).unwrap_or_else(|| node.clone()),
+        _ => node.clone(),
+    }
+}
+
+fn string_value(node: &SyntaxNode) -> Option<String> {
+    let string = syntax::ast::String::cast(node.clone())?;
+    string.string_parts().map(|part| match part {
+        syntax::ast::StringPart::Fragment(text) => Some(text.text().to_string()),
+        _ => None,
+    }).collect()
+}
+
+fn describe(option_type: &OptionType) -> String {
+    match option_type {
+        OptionType::Bool => "boolean".to_string(),
+        OptionType::Int { min: Some(min), max: Some(max) } => format!("integer between {min} and {max}"),
+        OptionType::Int { min: Some(min), max: None } => format!("integer of at least {min}"),
+        OptionType::Int { .. } => "integer".to_string(),
+        OptionType::Float => "number".to_string(),
+        OptionType::Str => "string".to_string(),
+        OptionType::Path => "path".to_string(),
+        OptionType::Package => "package".to_string(),
+        OptionType::Enum(values) => format!("one of {}", values.join(", ")),
+        OptionType::ListOf(inner) => format!("list of {}", describe(inner)),
+        OptionType::AttrsOf(inner) => format!("attribute set of {}", describe(inner)),
+        OptionType::NullOr(inner) => format!("null or {}", describe(inner)),
+        OptionType::Either(alternatives) => alternatives.iter()
+            .map(|alternative| match alternative {
+                OptionType::ListOf(_) | OptionType::AttrsOf(_) | OptionType::NullOr(_) | OptionType::Either(_) => {
+                    format!("({})", describe(alternative))
+                },
+                _ => describe(alternative),
+            })
+            .collect::<Vec<String>>()
+            .join(" or "),
+        OptionType::Submodule => "attribute set".to_string(),
+        OptionType::Other(text) => text.clone(),
+    }
+}
+
+/// Checks a value against an option type as far as that is possible without
+/// evaluating it, returning a description of the mismatch.
+pub fn validate(node: &SyntaxNode, option_type: &OptionType) -> Result<(), String> {
+    let node = unwrap_parens(node);
+    let Some(kind) = value_kind(&node) else {
+        return Ok(());
+    };
+    let mismatch = || Err(format!("expected {}, found {kind}", describe(option_type)));
+    match option_type {
+        OptionType::Bool => if kind == "boolean" { Ok(()) } else { mismatch() },
+        OptionType::Int { min, max } => {
+            if kind != "integer" {
+                return mismatch();
+            }
+            let Ok(value) = node.text().to_string().parse::<i64>() else {
+                return Ok(());
+            };
+            if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+                return Err(format!("{value} is not an {}", describe(option_type)));
+            }
+            Ok(())
+        },
+        OptionType::Float => if matches!(kind, "integer" | "float") { Ok(()) } else { mismatch() },
+        OptionType::Str => if kind == "string" { Ok(()) } else { mismatch() },
+        OptionType::Path => if matches!(kind, "path" | "string") { Ok(()) } else { mismatch() },
+        OptionType::Package => if matches!(kind, "attribute set" | "path" | "string") { Ok(()) } else { mismatch() },
+        OptionType::Enum(values) => {
+            let value = match kind {
+                "string" => string_value(&node),
+                "integer" | "float" => Some(node.text().to_string()),
+                "boolean" => Some(node.text().to_string()),
+                _ => return mismatch(),
+            };
+            match value {
+                Some(value) if !values.contains(&value) => Err(format!("{value} is not {}", describe(option_type))),
+                _ => Ok(()),
+            }
+        },
+        OptionType::ListOf(inner) => {
+            if kind != "list" {
+                return mismatch();
+            }
+            node.children().enumerate().try_for_each(|(i, element)| {
+                validate(&element, inner).map_err(|e| format!("element {}: {e}", i + 1))
+            })
+        },
+        OptionType::AttrsOf(inner) => {
+            if kind != "attribute set" {
+                return mismatch();
+            }
+            syntax::ast::AttrSet::cast(node.clone())
+                .into_iter()
+                .flat_map(|set| syntax::ast::HasBindings::bindings(&set).collect::<Vec<_>>())
+                .filter_map(|binding| match binding {
+                    syntax::ast::Binding::AttrpathValue(attr) => Some(attr),
+                    _ => None,
+                })
+                .try_for_each(|attr| {
+                    let (Some(attrpath), Some(value)) = (attr.attrpath(), attr.value()) else {
+                        return Ok(());
+                    };
+                    if attrpath.attrs().count() > 1 {
+                        return Ok(());
+                    }
+                    validate(value.syntax(), inner)
+                        .map_err(|e| format!("{}: {e}", attrpath.syntax().text()))
+                })
+        },
+        OptionType::NullOr(inner) => if kind == "null" { Ok(()) } else { validate(&node, inner) },
+        OptionType::Either(alternatives) => {
+            if alternatives.iter().any(|alternative| validate(&node, alternative).is_ok()) {
+                Ok(())
+            } else {
+                mismatch()
+            }
+        },
+        OptionType::Submodule => if kind == "attribute set" { Ok(()) } else { mismatch() },
+        OptionType::Other(_) => Ok(()),
+    }
+}
+
+/// The option path of the binding `node` is the value of, if that binding is
+/// below the options root.
+pub fn option_path_of_value(node: &SyntaxNode, root: &[String]) -> Option<Vec<String>> {
+    let binding = syntax::ast::AttrpathValue::cast(node.parent()?)?;
+    if binding.value()?.syntax() != node {
+        return None;
+    }
+    let set = binding.syntax().parent()?;
+    let mut path = option_prefix(root, &set_key(&set)?)?;
+    path.extend(attrpath_keys(&binding.attrpath()?));
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(name: &str, type_description: &str) -> OptionDecl {
+        OptionDecl {
+            name: name.to_string(),
+            path: option_path(name),
+            type_description: type_description.to_string(),
+            option_type: OptionType::parse(type_description),
+            description: String::new(),
+            default: None,
+            example: None,
+            read_only: false,
+        }
+    }
+
+    fn options() -> OptionSet {
+        OptionSet::new(vec![
+            option("boot.kernel.sysctl.\"net.ipv4.tcp_keepalive_time\"", "signed integer"),
+            option("services.openssh.enable", "boolean"),
+            option("services.openssh.ports", "list of 16 bit unsigned integer; between 0 and 65535 (both inclusive)"),
+            option("services.nginx.virtualHosts.<name>.root", "null or path"),
+        ])
+    }
+
+    fn path(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|segment| segment.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_quoted_segments_of_option_names() {
+        assert_eq!(
+            option_path("boot.kernel.sysctl.\"net.ipv4.tcp_keepalive_time\""),
+            path(&["boot", "kernel", "sysctl", "net.ipv4.tcp_keepalive_time"]),
+        );
+        assert_eq!(option_path("a.<name>.b"), path(&["a", "<name>", "b"]));
+        assert!(options().find(&path(&["boot", "kernel", "sysctl", "net.ipv4.tcp_keepalive_time"])).is_some());
+    }
+
+    #[test]
+    fn finds_options_through_placeholders() {
+        let options = options();
+        let root = options.find(&path(&["services", "nginx", "virtualHosts", "example.org", "root"])).unwrap();
+        assert_eq!(root.name, "services.nginx.virtualHosts.<name>.root");
+        assert_eq!(options.below(&path(&["services", "openssh"])).count(), 2);
+    }
+
+    #[test]
+    fn completes_one_segment_at_a_time() {
+        let options = options();
+        assert_eq!(options.completions(&[], ""), ["boot", "services"]);
+        assert_eq!(options.completions(&[], "services.op"), ["services.nginx", "services.openssh"]);
+        assert_eq!(options.completions(&path(&["services"]), "openssh."), ["openssh.enable", "openssh.ports"]);
+        assert_eq!(
+            options.completions(&path(&["boot", "kernel"]), "sysctl."),
+            ["sysctl.\"net.ipv4.tcp_keepalive_time\""],
+        );
+        assert!(options.completions(&path(&["services", "nginx"]), "virtualHosts.").is_empty());
+    }
+
+    fn value(source: &str) -> SyntaxNode {
+        crate::ast::source_expr(&syntax::parse_file(source).syntax_node()).unwrap()
+    }
+
+    fn check(source: &str, type_description: &str) -> Result<(), String> {
+        validate(&value(source), &OptionType::parse(type_description))
+    }
+
+    #[test]
+    fn parses_integer_ranges() {
+        assert_eq!(
+            OptionType::parse("16 bit unsigned integer; between 0 and 65535 (both inclusive)"),
+            OptionType::Int { min: Some(0), max: Some(65535) },
+        );
+        assert_eq!(OptionType::parse("unsigned integer, meaning >=0"), OptionType::Int { min: Some(0), max: None });
+        assert_eq!(OptionType::parse("signed integer"), OptionType::Int { min: None, max: None });
+    }
+
+    #[test]
+    fn parses_nullable_and_alternative_types() {
+        assert_eq!(
+            OptionType::parse("null or (list of string)"),
+            OptionType::NullOr(Box::new(OptionType::ListOf(Box::new(OptionType::Str)))),
+        );
+        let either = OptionType::parse("(list of string) or string");
+        assert_eq!(either, OptionType::Either(vec![OptionType::ListOf(Box::new(OptionType::Str)), OptionType::Str]));
+        assert_eq!(either.template(), "[ ]");
+        assert_eq!(describe(&either), "(list of string) or string");
+    }
+
+    #[test]
+    fn parses_enums() {
+        let one_of = OptionType::parse("one of \"a\", \"b\"");
+        assert_eq!(one_of, OptionType::Enum(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(one_of.template(), "\"a\"");
+        assert_eq!(OptionType::parse("one of \"x or y\", \"z\""), OptionType::Enum(vec!["x or y".to_string(), "z".to_string()]));
+    }
+
+    #[test]
+    fn splits_only_outside_parentheses_and_quotes() {
+        assert_eq!(split_top_level("(a or b) or c", " or "), ["(a or b)", "c"]);
+        assert_eq!(split_top_level("one of \"a or b\"", " or "), ["one of \"a or b\""]);
+        assert_eq!(strip_parens(" (list of string) "), "list of string");
+        assert_eq!(strip_parens("(a) or (b)"), "(a) or (b)");
+        assert_eq!(strip_parens("((a))"), "(a)");
+    }
+
+    #[test]
+    fn accepts_matching_values() {
+        assert_eq!(check("true", "boolean"), Ok(()));
+        assert_eq!(check("443", "16 bit unsigned integer; between 0 and 65535 (both inclusive)"), Ok(()));
+        assert_eq!(check("null", "null or (list of string)"), Ok(()));
+        assert_eq!(check("[ \"a\" ]", "(list of string) or string"), Ok(()));
+        assert_eq!(check("\"a\"", "(list of string) or string"), Ok(()));
+        assert_eq!(check("\"b\"", "one of \"a\", \"b\""), Ok(()));
+        // References can't be checked without evaluating them.
+        assert_eq!(check("config.port", "boolean"), Ok(()));
+    }
+
+    #[test]
+    fn describes_mismatches() {
+        assert_eq!(check("\"yes\"", "boolean"), Err("expected boolean, found string".to_string()));
+        assert_eq!(
+            check("70000", "16 bit unsigned integer; between 0 and 65535 (both inclusive)"),
+            Err("70000 is not an integer between 0 and 65535".to_string()),
+        );
+        assert_eq!(
+            check("[ \"a\" 1 ]", "null or (list of string)"),
+            Err("element 2: expected string, found integer".to_string()),
+        );
+        assert_eq!(
+            check("1", "(list of string) or string"),
+            Err("expected (list of string) or string, found integer".to_string()),
+        );
+        assert_eq!(check("\"c\"", "one of \"a\", \"b\""), Err("c is not one of a, b".to_string()));
+    }
+
+    #[test]
+    fn suggests_close_names_for_unknown_segments() {
+        let options = options();
+        assert_eq!(
+            options.unknown_segment(&path(&["services", "opensh", "enable"])),
+            Some((1, vec!["openssh".to_string()])),
+        );
+        assert_eq!(options.unknown_segment(&path(&["services", "openssh", "enable"])), None);
+        // Below an option the path is part of its value.
+        assert_eq!(options.unknown_segment(&path(&["services", "openssh", "enable", "x"])), None);
+        assert_eq!(options.unknown_segment(&path(&["servces"])), Some((0, vec!["services".to_string()])));
+    }
+
+    #[test]
+    fn measures_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
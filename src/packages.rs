@@ -0,0 +1,261 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Package {
+    /// Attribute path below `pkgs`, e.g. `python3Packages.requests`.
+    pub attr: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageError(pub String);
+
+impl std::fmt::Display for PackageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "packages: {}", self.0)
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct RawMeta {
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawPackage {
+    #[serde(default)]
+    pname: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    meta: RawMeta,
+}
+
+/// The packages of all channels, loaded from `nix-env -qa --json` or a saved
+/// listing.
+#[derive(Clone, Copy)]
+pub struct PackageIndex(pub Signal<Vec<Package>>);
+
+fn cache_file() -> Option<PathBuf> {
+    let cache = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache.join("declaro").join("packages.json"))
+}
+
+fn parse_packages(json: &str) -> Result<Vec<Package>, PackageError> {
+    let raw: std::collections::BTreeMap<String, RawPackage> = serde_json::from_str(json)
+        .map_err(|e| PackageError(e.to_string()))?;
+    // Listings of the channels prefix every attribute with the channel name,
+    // e.g. `nixpkgs.hello`; listings of a file (`nix-env -f '<nixpkgs>'`) don't.
+    let prefixed = raw.keys().all(|attr| attr.contains('.'));
+    Ok(raw.into_iter()
+        .map(|(attr, raw)| Package {
+            attr: match attr.split_once('.') {
+                Some((_, rest)) if prefixed => rest.to_string(),
+                _ => attr,
+            },
+            name: if raw.pname.is_empty() { raw.name } else { raw.pname },
+            version: raw.version,
+            description: raw.meta.description.unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// The package list from the last query or loaded listing, which works
+/// without network access. `None` when nothing has been cached yet.
+pub fn cached_packages() -> Result<Option<Vec<Package>>, PackageError> {
+    let Some(file) = cache_file() else {
+        return Ok(None);
+    };
+    let contents = match fs::read_to_string(&file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(PackageError(format!("{}: {e}", file.display()))),
+    };
+    parse_packages(&contents)
+        .map(Some)
+        .map_err(|e| PackageError(format!("{}: {}", file.display(), e.0)))
+}
+
+/// Packages together with why they could not be cached for use without
+/// network access, if they couldn't.
+pub struct QueriedPackages {
+    pub packages: Vec<Package>,
+    pub cache_error: Option<PackageError>,
+}
+
+fn cache(json: &str) -> Option<PackageError> {
+    cache_file()
+        .ok_or_else(|| PackageError("could not cache packages: no cache directory".to_string()))
+        .and_then(|file| {
+            file.parent()
+                .map(fs::create_dir_all)
+                .transpose()
+                .and_then(|_| fs::write(&file, json.as_bytes()))
+                .map_err(|e| PackageError(format!("could not cache packages: {e}")))
+        })
+        .err()
+}
+
+/// Queries the packages of the local channels and updates the cache.
+pub async fn query_packages() -> Result<QueriedPackages, PackageError> {
+    let output = tokio::process::Command::new("nix-env")
+        .args(["-qa", "--json", "--meta"])
+        .output()
+        .await
+        .map_err(|e| PackageError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(PackageError(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    let json = String::from_utf8_lossy(&output.stdout);
+    let packages = parse_packages(&json)?;
+    Ok(QueriedPackages { packages, cache_error: cache(&json) })
+}
+
+/// Reads a listing saved from `nix-env -qa --json --meta` and caches it like
+/// a query, for machines without channels or without nix.
+pub fn load_listing(file: &Path) -> Result<QueriedPackages, PackageError> {
+    let json = fs::read_to_string(file).map_err(|e| PackageError(format!("{}: {e}", file.display())))?;
+    let packages = parse_packages(&json)
+        .map_err(|e| PackageError(format!("{} is not a nix-env listing: {}", file.display(), e.0)))?;
+    Ok(QueriedPackages { packages, cache_error: cache(&json) })
+}
+
+// Higher is better. Every character of the query has to appear in order;
+// consecutive characters and matches at word starts score more.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<char>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous = None;
+    for c in query.to_lowercase().chars() {
+        let found = candidate[position..].iter().position(|&d| d == c)? + position;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(candidate[found - 1], '.' | '-' | '_') {
+            score += 3;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    // Prefer short names among equally good matches.
+    Some(score * 100 - candidate.len() as i64)
+}
+
+// Descriptions are long enough to contain most queries as a subsequence, so
+// they only count when they contain the query, and less than a name would.
+fn description_score(query: &str, description: &str) -> Option<i64> {
+    if !description.to_lowercase().contains(&query.to_lowercase()) {
+        return None;
+    }
+    fuzzy_score(query, description).map(|score| score / 4)
+}
+
+pub fn search<'a>(packages: &'a [Package], query: &str, limit: usize) -> Vec<&'a Package> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut hits = packages.iter()
+        .filter_map(|package| {
+            let score = fuzzy_score(query, &package.attr)
+                .max(fuzzy_score(query, &package.name))
+                .max(description_score(query, &package.description))?;
+            let exact = package.name.eq_ignore_ascii_case(query) || package.attr.eq_ignore_ascii_case(query);
+            Some((exact, score, package))
+        })
+        .collect::<Vec<_>>();
+    hits.sort_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)));
+    hits.into_iter().take(limit).map(|(_, _, package)| package).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(attr: &str, description: &str) -> Package {
+        Package {
+            attr: attr.to_string(),
+            name: attr.rsplit('.').next().unwrap_or(attr).to_string(),
+            version: "1.0".to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    fn attrs(hits: Vec<&Package>) -> Vec<&str> {
+        hits.into_iter().map(|package| package.attr.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_listings_with_and_without_channel_prefixes() {
+        let channels = r#"{
+            "nixpkgs.hello": { "pname": "hello", "version": "2.12", "meta": { "description": "Says hello" } },
+            "nixpkgs.python3Packages.requests": { "name": "python3.11-requests-2.31.0" }
+        }"#;
+        let packages = parse_packages(channels).unwrap();
+        assert_eq!(packages[0], Package {
+            attr: "hello".to_string(),
+            name: "hello".to_string(),
+            version: "2.12".to_string(),
+            description: "Says hello".to_string(),
+        });
+        assert_eq!(packages[1].attr, "python3Packages.requests");
+        assert_eq!(packages[1].name, "python3.11-requests-2.31.0");
+
+        let file = r#"{ "hello": {}, "python3Packages.requests": {} }"#;
+        let packages = parse_packages(file).unwrap();
+        assert_eq!(packages.iter().map(|p| p.attr.as_str()).collect::<Vec<_>>(), ["hello", "python3Packages.requests"]);
+
+        assert!(parse_packages("[]").is_err());
+    }
+
+    #[test]
+    fn scores_subsequences() {
+        assert_eq!(fuzzy_score("fx", "firefox"), Some(5 * 100 - 7));
+        assert_eq!(fuzzy_score("xf", "firefox"), None);
+        assert_eq!(fuzzy_score("", "firefox"), Some(-7));
+        // Consecutive characters and word starts score more.
+        assert!(fuzzy_score("fire", "firefox") > fuzzy_score("fire", "fooirxe"));
+        assert!(fuzzy_score("req", "python3Packages.requests") > fuzzy_score("req", "prequel"));
+        assert_eq!(fuzzy_score("FIRE", "firefox"), fuzzy_score("fire", "FireFox"));
+    }
+
+    #[test]
+    fn ranks_exact_names_first() {
+        let packages = vec![
+            package("gitFull", "Distributed version control system"),
+            package("git", "Distributed version control system"),
+            package("gitui", "Terminal UI for git"),
+            package("tig", "Text-mode interface for git"),
+        ];
+        let hits = attrs(search(&packages, "git", 10));
+        assert_eq!(hits[0], "git");
+        assert_eq!(hits.len(), 4);
+        assert_eq!(attrs(search(&packages, "git", 2)).len(), 2);
+        assert!(search(&packages, "  ", 10).is_empty());
+    }
+
+    #[test]
+    fn matches_descriptions_only_as_substrings() {
+        let packages = vec![
+            package("ripgrep", "Utility that combines the usability of The Silver Searcher with grep"),
+            package("hello", "A program that produces a familiar, friendly greeting"),
+        ];
+        assert_eq!(attrs(search(&packages, "silver", 10)), ["ripgrep"]);
+        // "grep" is a subsequence of the greeting, but not part of it.
+        assert_eq!(attrs(search(&packages, "grep", 10)), ["ripgrep"]);
+    }
+}
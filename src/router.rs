@@ -3,6 +3,15 @@ use dioxus_motion::prelude::*;
 use dioxus_motion::transitions::page_transitions::TransitionVariantResolver;
 
 use crate::components::NodeUI;
+use crate::components::source_view::{SourcePane, SourcePaneVisible};
+use crate::components::diff_view::DiffView;
+use crate::components::git_panel::GitPanel;
+use crate::components::flake_view::FlakeView;
+use crate::components::outline::{OutlineSidebar, OutlineVisible};
+use crate::components::search::{SearchPanel, SearchVisible};
+use crate::diff::DiffBase;
+use crate::git::GitMode;
+use crate::flake::FlakeMode;
 use crate::ast::AstPath;
 
 #[derive(Clone, Debug, PartialEq, Routable, MotionTransitions)]
@@ -13,6 +22,14 @@ pub enum Route {
         #[transition(SlideRight)]
         NodeUI { path: AstPath },
 
+        #[route("/diff")]
+        #[transition(Fade)]
+        DiffView {},
+
+        #[route("/flake")]
+        #[transition(Fade)]
+        FlakeView {},
+
         #[route("/")]
         Home {}
 
@@ -35,8 +52,49 @@ pub fn Wrapper() -> Element {
         }
     });
     use_context_provider(|| resolver);
+    let source_visible = use_context::<SourcePaneVisible>().0;
+    let diff_base = use_context::<Signal<Option<DiffBase>>>();
+    let git_mode = use_context::<GitMode>().0;
+    let outline_visible = use_context::<OutlineVisible>().0;
+    let search_visible = use_context::<SearchVisible>().0;
+    let flake_mode = use_context::<FlakeMode>().0;
+    let mut was_flake_mode = use_signal(|| false);
+    let nav = navigator();
+    use_effect(move || {
+        if diff_base.read().is_some() {
+            nav.push(Route::DiffView {});
+        }
+    });
+    use_effect(move || {
+        // Only navigate back when the mode is switched off, not on the first run.
+        if flake_mode() {
+            was_flake_mode.set(true);
+            nav.push(Route::FlakeView {});
+        } else if *was_flake_mode.peek() {
+            was_flake_mode.set(false);
+            nav.push(Route::NodeUI { path: AstPath { indices: vec![0] } });
+        }
+    });
     rsx! {
-        AnimatedOutlet::<Route> {}
+        div {
+            class: if source_visible() || outline_visible() { "split-pane" } else { "single-pane" },
+            if outline_visible() {
+                OutlineSidebar {}
+            }
+            div {
+                class: "graphical-pane",
+                if git_mode() {
+                    GitPanel {}
+                }
+                if search_visible() {
+                    SearchPanel {}
+                }
+                AnimatedOutlet::<Route> {}
+            }
+            if source_visible() {
+                SourcePane {}
+            }
+        }
     }
 }
 
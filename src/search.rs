@@ -0,0 +1,137 @@
+use syntax::{SyntaxKind, SyntaxNode};
+use syntax::ast::{AstNode, HasStringParts};
+
+use crate::ast::attrpath_keys;
+
+const MAX_HITS: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitKind {
+    Attribute,
+    String,
+    Reference,
+}
+
+impl HitKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HitKind::Attribute => "attribute",
+            HitKind::String => "string",
+            HitKind::Reference => "reference",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    pub kind: HitKind,
+    pub text: String,
+    /// Attribute names of the bindings enclosing the hit.
+    pub context: Vec<String>,
+    /// The expression to jump to.
+    pub node: SyntaxNode,
+}
+
+/// Attribute names of all bindings enclosing `node`, outermost first.
+/// Shorthand paths like `a.b.c = v` contribute one name per segment.
+pub fn enclosing_keys(node: &SyntaxNode) -> Vec<String> {
+    let mut keys = node.ancestors()
+        .skip(1)
+        .filter_map(syntax::ast::AttrpathValue::cast)
+        .filter_map(|attr| attr.attrpath())
+        .map(|ap| attrpath_keys(&ap))
+        .collect::<Vec<Vec<String>>>();
+    keys.reverse();
+    keys.concat()
+}
+
+fn matches_segments(full: &[String], own_start: usize, query: &[String]) -> bool {
+    if query.is_empty() || query.len() > full.len() {
+        return false;
+    }
+    let (last, init) = query.split_last().unwrap();
+    // The match has to end in the binding's own attrpath, otherwise every
+    // binding below a matching set would be a hit as well.
+    (own_start.max(query.len() - 1)..full.len()).any(|end| {
+        let start = end + 1 - query.len();
+        full[start..end].iter().zip(init).all(|(segment, q)| segment.to_lowercase() == *q)
+            && full[end].to_lowercase().contains(last.as_str())
+    })
+}
+
+pub fn search(root: &SyntaxNode, query: &str) -> Vec<SearchHit> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let segments = query.split('.').map(|s| s.trim().to_string()).collect::<Vec<String>>();
+    root.descendants()
+        .filter_map(|node| {
+            match node.kind() {
+                SyntaxKind::ATTR_PATH_VALUE => {
+                    let attr = syntax::ast::AttrpathValue::cast(node.clone())?;
+                    let own = attrpath_keys(&attr.attrpath()?);
+                    let context = enclosing_keys(&node);
+                    let full = [context.clone(), own.clone()].concat();
+                    if !matches_segments(&full, context.len(), &segments) {
+                        return None;
+                    }
+                    Some(SearchHit {
+                        kind: HitKind::Attribute,
+                        text: own.join("."),
+                        context,
+                        node: attr.value()?.syntax().clone(),
+                    })
+                },
+                SyntaxKind::STRING | SyntaxKind::INDENT_STRING => {
+                    let text = syntax::ast::String::cast(node.clone())
+                        .map(|s| string_text(&s))
+                        .or_else(|| syntax::ast::IndentString::cast(node.clone()).map(|s| string_text(&s)))?;
+                    if !text.to_lowercase().contains(&query) {
+                        return None;
+                    }
+                    // Strings used as attribute names are found as attributes.
+                    if node.parent().is_some_and(|p| p.kind() == SyntaxKind::ATTR_PATH) {
+                        return None;
+                    }
+                    Some(SearchHit {
+                        kind: HitKind::String,
+                        text,
+                        context: enclosing_keys(&node),
+                        node,
+                    })
+                },
+                SyntaxKind::REF | SyntaxKind::SELECT => {
+                    // Only the outermost selection of `a.b.c` is reported.
+                    if node.kind() == SyntaxKind::REF
+                        && node.parent().is_some_and(|p| p.kind() == SyntaxKind::SELECT) {
+                        return None;
+                    }
+                    let text = node.text().to_string();
+                    if !text.to_lowercase().contains(&query) {
+                        return None;
+                    }
+                    Some(SearchHit {
+                        kind: HitKind::Reference,
+                        text,
+                        context: enclosing_keys(&node),
+                        node,
+                    })
+                },
+                _ => None,
+            }
+        })
+        .take(MAX_HITS)
+        .collect()
+}
+
+fn string_text(string: &impl HasStringParts) -> String {
+    string.string_parts()
+        .map(|part| match part {
+            syntax::ast::StringPart::Fragment(text) => text.text().to_string(),
+            syntax::ast::StringPart::Escape(escape) => escape.text().to_string(),
+            syntax::ast::StringPart::Dynamic(dynamic) => dynamic.syntax().text().to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("")
+}